@@ -0,0 +1,432 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use log::LevelFilter;
+use crosswordo_rust::{Budget, DictionaryBackend, Direction, PlacementStrategy, MAX};
+use crosswordo_rust::render::svg::SvgOptions;
+
+#[derive(Parser)]
+#[command(name = "crosswordo", version, about = "Word search puzzle generator with a hidden solution sentence")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Number of rows of the board
+    #[arg(short, long, default_value_t = 4)]
+    pub rows: usize,
+
+    /// Number of columns of the board
+    #[arg(short, long, default_value_t = 6)]
+    pub cols: usize,
+
+    /// Template file with a row per line, letters are kept, '?' is filled and '#' stays blocked. Overrides rows and cols
+    #[arg(short, long, value_name = "PATH")]
+    pub template: Option<PathBuf>,
+
+    /// Dictionary file with a word per line, can be repeated. Bundled dictionary of --language is used when omitted
+    #[arg(short, long = "dictionary", value_name = "PATH")]
+    pub dictionaries: Vec<PathBuf>,
+
+    /// Language of the bundled dictionary
+    #[arg(short, long, value_enum, default_value_t = Language::En)]
+    pub language: Language,
+
+    /// Load the dictionary into a packed trie which uses a fraction of the memory
+    #[arg(long)]
+    pub compact_dictionary: bool,
+
+    /// Dictionary written by compile-dict, used instead of dictionary files. Word length options are applied when compiling
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["dictionaries", "compact_dictionary"])]
+    pub compiled_dictionary: Option<PathBuf>,
+
+    /// Hidden solution text, non alphabetic characters are removed
+    #[arg(short, long, default_value = "stolicka")]
+    pub solution: String,
+
+    /// Where on board the solution letters are put
+    #[arg(long, value_enum, default_value_t = Placement::Clusters)]
+    pub placement: Placement,
+
+    /// Theme words which have to appear on board, comma separated and can be repeated
+    #[arg(long = "require", value_name = "WORDS", value_delimiter = ',')]
+    pub required_words: Vec<String>,
+
+    /// Shortest word which can be placed on board
+    #[arg(long, default_value_t = MAX)]
+    pub min_length: usize,
+
+    /// Longest word which can be placed on board
+    #[arg(long)]
+    pub max_length: Option<usize>,
+
+    /// Directions in which words are placed, comma separated names (east,south) or presets all and forward
+    #[arg(long, value_delimiter = ',', default_value = "all", value_parser = parse_directions)]
+    pub directions: Vec<Vec<Direction>>,
+
+    /// Seed of the random generator, the same seed reproduces the same puzzle
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Stop the generation after this many milliseconds
+    #[arg(long, value_name = "MS")]
+    pub time_limit: Option<u64>,
+
+    /// Stop the generation after this many words were put on board
+    #[arg(long, value_name = "COUNT")]
+    pub max_placements: Option<usize>,
+
+    /// Stop the generation after this many words were removed from board
+    #[arg(long, value_name = "COUNT")]
+    pub max_backtracks: Option<usize>,
+
+    /// Return the board with the most covered cells when generation fails or runs out of budget
+    #[arg(long)]
+    pub partial: bool,
+
+    /// Fill cells no word covers with random letters, weighted by letter frequency of the dictionary or picked evenly from the given alphabet
+    #[arg(long, value_name = "ALPHABET", num_args = 0..=1)]
+    pub filler: Option<Option<String>>,
+
+    /// Fail when a placed word can be found on board more than once instead of only warning about it
+    #[arg(long)]
+    pub reject_duplicates: bool,
+
+    /// Words never share letters with other words
+    #[arg(long)]
+    pub no_crossings: bool,
+
+    /// Words can pass through solution cells holding the same letter, the solution cannot be read from the cells left over then
+    #[arg(long)]
+    pub solution_crossings: bool,
+
+    /// Preferred share of word letters crossing other words, from 0 (avoid overlaps) to 1 (seek them)
+    #[arg(long, value_name = "DENSITY", value_parser = parse_density)]
+    pub overlap_density: Option<f64>,
+
+    /// Words can end before the edge of board or a blocked cell instead of filling every free cell in their direction
+    #[arg(long)]
+    pub short_words: bool,
+
+    /// Format of the generated puzzle
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// File to write the puzzle to, standard output is used when omitted
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// File to write the SVG answer key to
+    #[arg(long, value_name = "PATH")]
+    pub answer_key: Option<PathBuf>,
+
+    /// Font family of rendered SVG pages
+    #[arg(long)]
+    pub font: Option<String>,
+
+    /// Size of a grid cell in rendered SVG pages
+    #[arg(long)]
+    pub cell_size: Option<f32>,
+
+    /// Margin around rendered SVG pages
+    #[arg(long)]
+    pub margin: Option<f32>,
+
+    /// Log progress to standard error, repeat for more detail (-vvv prints every step of backtracking)
+    #[arg(short, long, action = ArgAction::Count)]
+    pub verbose: u8,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Check a grid against its word list and reveal the hidden message, generation options are ignored
+    Solve(SolveArgs),
+    /// Write the words of --dictionary or --language passing the word length options into a binary dictionary for --compiled-dictionary
+    CompileDict(CompileDictArgs),
+}
+
+#[derive(Args)]
+pub struct CompileDictArgs {
+    /// File the compiled dictionary is written to
+    #[arg(short, long, value_name = "PATH")]
+    pub output: PathBuf,
+}
+
+#[derive(Args)]
+pub struct SolveArgs {
+    /// Grid file with a row per line, '#' marks a blocked cell
+    #[arg(short, long, value_name = "PATH")]
+    pub grid: PathBuf,
+
+    /// Word list file with a word per line
+    #[arg(short, long, value_name = "PATH")]
+    pub words: PathBuf,
+
+    /// Directions in which words are searched, comma separated names (east,south) or presets all and forward
+    #[arg(long, value_delimiter = ',', default_value = "all", value_parser = parse_directions)]
+    pub directions: Vec<Vec<Direction>>,
+}
+
+impl SolveArgs {
+    pub fn allowed_directions(&self) -> Vec<Direction> {
+        unique_directions(&self.directions)
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Language {
+    En, Sk
+}
+
+impl Language {
+    pub fn dictionary_path(&self) -> PathBuf {
+        match self {
+            Language::En => PathBuf::from("./res/en.dr"),
+            Language::Sk => PathBuf::from("./res/sk.dr"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Placement {
+    /// Equal runs of cells in reading order with a letter in each
+    Clusters,
+    /// Anywhere on board
+    Uniform,
+    /// Second half of board
+    End,
+    /// Edges of board
+    Border,
+    /// Consecutive cells of a spiral from the top left corner
+    Spiral,
+    /// Consecutive cells at the bottom of board
+    Bottom,
+}
+
+impl From<Placement> for PlacementStrategy {
+    fn from(placement: Placement) -> Self {
+        match placement {
+            Placement::Clusters => PlacementStrategy::Clusters,
+            Placement::Uniform => PlacementStrategy::Uniform,
+            Placement::End => PlacementStrategy::End,
+            Placement::Border => PlacementStrategy::Border,
+            Placement::Spiral => PlacementStrategy::Spiral,
+            Placement::Bottom => PlacementStrategy::Bottom,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Text, Json, Svg
+}
+
+// Single direction name or a preset of directions
+fn parse_directions(value: &str) -> Result<Vec<Direction>, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "all" => Ok(Direction::ALL().to_vec()),
+        "forward" => Ok(Direction::FORWARD().to_vec()),
+        name => Direction::fromName(name)
+            .filter(|direction| *direction != Direction::CENTER())
+            .map(|direction| vec![direction])
+            .ok_or(format!("unknown direction '{}'", value)),
+    }
+}
+
+fn parse_density(value: &str) -> Result<f64, String> {
+    let density: f64 = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+    if !(0.0..=1.0).contains(&density) {
+        return Err(format!("density {} is not between 0 and 1", density));
+    }
+
+    Ok(density)
+}
+
+fn unique_directions(presets: &[Vec<Direction>]) -> Vec<Direction> {
+    let mut directions: Vec<Direction> = Vec::new();
+    for direction in presets.iter().flatten() {
+        if !directions.contains(direction) {
+            directions.push(*direction);
+        }
+    }
+
+    directions
+}
+
+impl Cli {
+    pub fn dictionary_paths(&self) -> Vec<PathBuf> {
+        if self.dictionaries.is_empty() {
+            return vec![self.language.dictionary_path()];
+        }
+
+        self.dictionaries.clone()
+    }
+
+    pub fn allowed_directions(&self) -> Vec<Direction> {
+        unique_directions(&self.directions)
+    }
+
+    pub fn budget(&self) -> Budget {
+        Budget {
+            time_limit: self.time_limit.map(Duration::from_millis),
+            max_placements: self.max_placements,
+            max_backtracks: self.max_backtracks,
+        }
+    }
+
+    pub fn dictionary_backend(&self) -> DictionaryBackend {
+        if self.compact_dictionary {
+            return DictionaryBackend::Packed;
+        }
+
+        DictionaryBackend::Trie
+    }
+
+    pub fn log_level(&self) -> LevelFilter {
+        match self.verbose {
+            0 => LevelFilter::Warn,
+            1 => LevelFilter::Info,
+            2 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    }
+
+    pub fn svg_options(&self) -> SvgOptions {
+        let mut options = SvgOptions::default();
+        if let Some(font) = &self.font {
+            options.font_family = font.clone();
+        }
+        if let Some(cell_size) = self.cell_size {
+            options.font_size = options.font_size * cell_size / options.cell_size;
+            options.cell_size = cell_size;
+        }
+        if let Some(margin) = self.margin {
+            options.margin = margin;
+        }
+
+        options
+    }
+
+    // Word length filter applied to the dictionary, words shorter than MAX are never placed
+    pub fn accepts_length(&self, length: usize) -> bool {
+        length >= self.min_length.max(MAX) && self.max_length.is_none_or(|max_length| length <= max_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_command() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_defaults() {
+        let cli = Cli::try_parse_from(["crosswordo"]).unwrap();
+
+        assert_eq!((cli.rows, cli.cols), (4, 6));
+        assert_eq!(cli.dictionary_paths(), vec![PathBuf::from("./res/en.dr")]);
+        assert_eq!(cli.allowed_directions(), Direction::ALL().to_vec());
+        assert_eq!(cli.log_level(), LevelFilter::Warn);
+        assert_eq!(cli.budget(), Budget::default());
+        assert_eq!(cli.filler, None);
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let cli = Cli::try_parse_from(["crosswordo", "--no-crossings", "--overlap-density", "0.25"]).unwrap();
+        assert!(cli.no_crossings);
+        assert_eq!(cli.overlap_density, Some(0.25));
+
+        assert!(Cli::try_parse_from(["crosswordo", "--overlap-density", "1.5"]).is_err());
+
+        let cli = Cli::try_parse_from(["crosswordo", "--short-words"]).unwrap();
+        assert!(cli.short_words);
+
+        let cli = Cli::try_parse_from(["crosswordo", "--placement", "spiral"]).unwrap();
+        assert_eq!(PlacementStrategy::from(cli.placement), PlacementStrategy::Spiral);
+    }
+
+    #[test]
+    fn test_solve() {
+        let cli = Cli::try_parse_from(["crosswordo", "solve", "-g", "grid.txt", "-w", "words.txt", "--directions", "forward"]).unwrap();
+
+        let Some(Command::Solve(args)) = cli.command else { panic!("solve subcommand expected") };
+        assert_eq!(args.grid, PathBuf::from("grid.txt"));
+        assert_eq!(args.words, PathBuf::from("words.txt"));
+        assert_eq!(args.allowed_directions(), Direction::FORWARD().to_vec());
+
+        assert!(Cli::try_parse_from(["crosswordo", "solve", "-g", "grid.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_compile_dict() {
+        let cli = Cli::try_parse_from(["crosswordo", "--language", "sk", "--min-length", "4", "compile-dict", "--output", "sk.dict"]).unwrap();
+        assert_eq!(cli.dictionary_paths(), vec![PathBuf::from("./res/sk.dr")]);
+        assert!(!cli.accepts_length(3));
+
+        let Some(Command::CompileDict(args)) = cli.command else { panic!("compile-dict subcommand expected") };
+        assert_eq!(args.output, PathBuf::from("sk.dict"));
+
+        let cli = Cli::try_parse_from(["crosswordo", "--compiled-dictionary", "sk.dict"]).unwrap();
+        assert_eq!(cli.compiled_dictionary, Some(PathBuf::from("sk.dict")));
+        assert!(Cli::try_parse_from(["crosswordo", "--compiled-dictionary", "sk.dict", "--dictionary", "words.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_filler() {
+        let cli = Cli::try_parse_from(["crosswordo", "--filler", "--partial"]).unwrap();
+        assert_eq!(cli.filler, Some(None));
+        assert!(cli.partial);
+        assert!(!cli.reject_duplicates);
+        assert!(!cli.no_crossings && !cli.solution_crossings);
+        assert_eq!(PlacementStrategy::from(cli.placement), PlacementStrategy::Clusters);
+        assert_eq!(cli.dictionary_backend(), DictionaryBackend::Trie);
+
+        let cli = Cli::try_parse_from(["crosswordo", "--filler", "abc"]).unwrap();
+        assert_eq!(cli.filler, Some(Some(String::from("abc"))));
+    }
+
+    #[test]
+    fn test_verbosity() {
+        let cli = Cli::try_parse_from(["crosswordo", "-vv"]).unwrap();
+
+        assert_eq!(cli.log_level(), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn test_directions() {
+        let cli = Cli::try_parse_from(["crosswordo", "--directions", "east,SOUTH,east"]).unwrap();
+        assert_eq!(cli.allowed_directions(), vec![Direction::EAST(), Direction::SOUTH()]);
+
+        let cli = Cli::try_parse_from(["crosswordo", "--directions", "forward"]).unwrap();
+        assert_eq!(cli.allowed_directions(), Direction::FORWARD().to_vec());
+
+        assert!(Cli::try_parse_from(["crosswordo", "--directions", "center"]).is_err());
+    }
+
+    #[test]
+    fn test_arguments() {
+        let cli = Cli::try_parse_from([
+            "crosswordo", "-r", "8", "-c", "10", "-d", "a.dr", "-d", "b.dr",
+            "-s", "hidden", "-t", "board.txt", "--require", "cat,dog", "--require", "owl", "--seed", "42", "--min-length", "4", "--max-length", "6", "-o", "out.txt",
+            "--time-limit", "2000", "--max-backtracks", "500", "--compact-dictionary"
+        ]).unwrap();
+
+        assert_eq!((cli.rows, cli.cols), (8, 10));
+        assert_eq!(cli.dictionary_paths(), vec![PathBuf::from("a.dr"), PathBuf::from("b.dr")]);
+        assert_eq!(cli.solution, "hidden");
+        assert_eq!(cli.seed, Some(42));
+        assert_eq!(cli.template, Some(PathBuf::from("board.txt")));
+        assert_eq!(cli.required_words, vec!["cat", "dog", "owl"]);
+        assert_eq!(cli.output, Some(PathBuf::from("out.txt")));
+        assert_eq!(cli.dictionary_backend(), DictionaryBackend::Packed);
+        assert!(!cli.accepts_length(3));
+        assert!(cli.accepts_length(6));
+        assert!(!cli.accepts_length(7));
+        assert_eq!(cli.budget(), Budget { time_limit: Some(Duration::from_secs(2)), max_placements: None, max_backtracks: Some(500) });
+    }
+}
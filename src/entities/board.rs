@@ -1,9 +1,19 @@
 use std::cmp::max;
-use std::collections::HashMap;
-use std::fmt::format;
+use std::collections::BTreeMap;
+use std::fmt;
+use log::{debug, trace};
 use crate::entities::direction::Direction;
 use crate::entities::solution::Solution;
+use crate::entities::template::{BLOCKED, Template};
 use crate::entities::word::Word;
+use crate::error::CrosswordoError;
+
+// Contribution of template letters, these are never removed from board
+const FIXED: usize = usize::MAX;
+// Contribution of random letters in cells no word covers
+const FILLER: usize = usize::MAX - 1;
+// Contribution of hidden message letters, words cannot pass these cells
+const SOLUTION: usize = usize::MAX - 2;
 
 pub struct Board {
     pub cols: usize,
@@ -11,43 +21,84 @@ pub struct Board {
     pub board: Vec<Vec<char>>,
     // will track each position with am info of first iteration
     contributions: Vec<Vec<Option<usize>>>,
-    tracker: usize
+    // Number of placed words passing each cell, template letters are not finished until a word covers them
+    coverage: Vec<Vec<usize>>,
+    tracker: usize,
+    // Directions in which sequences are read from a position
+    directions: Vec<Direction>,
+    // Words can share letters with words already on board
+    crossings: bool,
+    // Solution letters are never shared with words
+    strict_solution: bool,
 }
 
 impl Board {
     // Determine the function signature. The function should accept the dimensions of the grid (rows and columns) and return an initialized grid.
-    pub fn new(rows: usize, cols: usize) -> Board {
+    pub fn new(rows: usize, cols: usize) -> Result<Board, CrosswordoError> {
         // if cols > 10 || rows > 10 {
         //     panic!("The board seems too big to handle");
         // }
 
         if cols <= 3 || rows <= 3 {
-            panic!("The board seems too small to handle");
+            return Err(CrosswordoError::BoardTooSmall { rows, cols });
         }
 
-        return Board {
+        return Ok(Board {
             rows, cols,
             // Implement the logic to initialize the grid with wildcards ('*').
             board: vec![vec!['?'; cols]; rows],
             contributions: vec![vec![None; cols]; rows],
-            tracker: 0
-        }
+            coverage: vec![vec![0; cols]; rows],
+            tracker: 0,
+            directions: Direction::ALL().to_vec(),
+            crossings: true,
+            strict_solution: true,
+        })
     }
 
-    pub fn print_board(&self) {
+    pub fn with_directions(mut self, directions: &[Direction]) -> Self {
+        self.directions = directions.to_vec();
+        self
+    }
+
+    pub fn with_crossings(mut self, crossings: bool) -> Self {
+        self.crossings = crossings;
+        self
+    }
+
+    // Without strict solution a word can pass a solution cell holding the same letter, the solution then
+    // cannot be read from the cells left over
+    pub fn with_strict_solution(mut self, strict_solution: bool) -> Self {
+        self.strict_solution = strict_solution;
+        self
+    }
+
+    // Copies fixed letters and blocked cells of template, template needs to have the same size as board
+    pub fn with_template(mut self, template: &Template) -> Self {
         for row in 0..self.rows {
             for col in 0..self.cols {
-                print!("{}-{:0>2}\t", self.board[row][col], self.contributions[row][col].unwrap_or(99));
+                if template.is_fixed(row, col) {
+                    self.board[row][col] = template.cells[row][col];
+                    self.contributions[row][col] = Some(FIXED);
+                }
             }
-            println!();
         }
+        self
+    }
+
+    pub fn directions(&self) -> &[Direction] {
+        &self.directions
+    }
+
+    pub fn print_board(&self) {
+        print!("{}", self);
     }
 
     pub fn is_board_populated(&self) -> bool {
-        // if board has at least 1 questionmark it's not finished yet
+        // if board has at least 1 questionmark or template letter without word it's not finished yet
         for row in 0..self.rows {
             for col in 0..self.cols {
-                if Some(self.board[row][col]).unwrap() == '?' {
+                if self.is_open(row, col) {
                     return false
                 }
             }
@@ -57,14 +108,60 @@ impl Board {
 
     }
 
+    // Empty cells and template letters no word passes yet
+    pub fn count_empty_cells(&self) -> usize {
+        (0..self.rows)
+            .flat_map(|row| (0..self.cols).map(move |col| (row, col)))
+            .filter(|(row, col)| self.is_open(*row, *col))
+            .count()
+    }
+
+    // Cell which still needs a word, template letters would otherwise be read as a part of the solution
+    fn is_open(&self, row: usize, col: usize) -> bool {
+        match self.board[row][col] {
+            '?' => true,
+            BLOCKED => false,
+            _ => self.contributions[row][col] == Some(FIXED) && self.coverage[row][col] == 0,
+        }
+    }
+
     pub fn put_solution_on_board(&mut self, solution: &Solution) {
         for (position, c) in solution.locations.clone() {
             let row = position / self.cols;
             let col = position % self.cols;
 
-            // self.board[row][col] =  *c;
-            self.board[row][col] =  '*';
+            self.board[row][col] = c;
+            self.contributions[row][col] = Some(SOLUTION);
+        }
+    }
+
+    pub fn is_solution_cell(&self, row: usize, col: usize) -> bool {
+        self.contributions[row][col] == Some(SOLUTION)
+    }
+
+    // Cell holding a letter of a placed word
+    fn is_word_cell(&self, row: usize, col: usize) -> bool {
+        self.contributions[row][col].is_some_and(|contribution| contribution < SOLUTION)
+    }
+
+    // Cell no word can pass, it stops every sequence
+    fn is_obstacle(&self, row: usize, col: usize) -> bool {
+        if self.board[row][col] == BLOCKED {
+            return true;
         }
+        if self.is_solution_cell(row, col) {
+            return self.strict_solution;
+        }
+
+        return !self.crossings && self.is_word_cell(row, col);
+    }
+
+    // Number of letters of word which are already on board
+    pub fn count_overlaps(&self, word: &Word) -> usize {
+        word.cells()
+            .iter()
+            .filter(|(row, col)| *row < self.rows && *col < self.cols && self.board[*row][*col] != '?')
+            .count()
     }
 
     // TODO : direction, position, word
@@ -74,8 +171,8 @@ impl Board {
         self.put_sequence_on_board(w, row, col, direction);
         self.tracker += 1;
 
-        println!("===== putting {}. word {} at R{}C{}[{}] =====", self.tracker, word.clone().word, row, col, direction.getIndex());
-        self.print_board()
+        debug!("putting {}. word {} at R{}C{}[{}]", self.tracker, word.word, row, col, direction.getName());
+        trace!("board after putting {}:\n{}", word.word, self);
     }
 
     pub fn remove_word_from_board(&mut self, word: &Word) {
@@ -84,27 +181,79 @@ impl Board {
         self.tracker -= 1;
         self.remove_sequence_from_board(w, row, col, direction);
 
-        println!("===== removing {}. word {} at R{}C{}[{}] =====", self.tracker + 1, word.clone().word, row, col, direction.getIndex());
-        self.print_board()
+        debug!("removing {}. word {} at R{}C{}[{}]", self.tracker + 1, word.word, row, col, direction.getName());
+        trace!("board after removing {}:\n{}", word.word, self);
+    }
+
+    pub fn put_filler(&mut self, row: usize, col: usize, c: char) {
+        self.board[row][col] = c;
+        self.contributions[row][col] = Some(FILLER);
+    }
+
+    pub fn filler_cells(&self) -> Vec<(usize, usize)> {
+        let mut cells: Vec<(usize, usize)> = Vec::new();
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                if self.contributions[row][col] == Some(FILLER) {
+                    cells.push((row, col));
+                }
+            }
+        }
+
+        cells
     }
 
-    pub fn get_sequences_from_position(&self, row: usize, col: usize) -> Option<HashMap<Direction, String>> {
-        let c = self.board[row][col];
+    // All positions and allowed directions where word fits into empty cells or matches letters already on board
+    pub fn find_placements(&self, word: &str) -> Vec<Word> {
+        let mut placements: Vec<Word> = Vec::new();
+
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                for direction in &self.directions {
+                    if self.fits_on_board(word, row, col, direction) {
+                        placements.push(Word::new(word.to_string(), *direction, (row, col)));
+                    }
+                }
+            }
+        }
+
+        placements
+    }
+
+    fn fits_on_board(&self, word: &str, row: usize, col: usize, direction: &Direction) -> bool {
+        for (depth, c) in word.chars().enumerate() {
+            let row = i32::try_from(row).unwrap() + (direction.getRow() * i32::try_from(depth).unwrap());
+            let col = i32::try_from(col).unwrap() + (direction.getCol() * i32::try_from(depth).unwrap());
+
+            if row < 0 || col < 0 || row >= (i32::try_from(self.rows).unwrap()) || col >= (i32::try_from(self.cols).unwrap()) {
+                return false;
+            }
+
+            let cell = self.board[row as usize][col as usize];
+            if self.is_obstacle(row as usize, col as usize) || (cell != '?' && cell != c) {
+                return false;
+            }
+        }
 
-        if c == '*' {
+        return true;
+    }
+
+    pub fn get_sequences_from_position(&self, row: usize, col: usize) -> Option<BTreeMap<Direction, String>> {
+        if self.is_obstacle(row, col) {
             // return [Default::default(); 9];
             return None;
         }
 
         // let mut sequences: [String; 9] = [Default::default(); 9];
-        let mut sequences: HashMap<Direction, String> = HashMap::new();
+        // Ordered map keeps the directions in the same order between runs
+        let mut sequences: BTreeMap<Direction, String> = BTreeMap::new();
 
         let max_depth = max(i32::try_from(self.rows).unwrap(), i32::try_from(self.cols).unwrap());
 
         // first check if it's not a solution stuff
-        for DIRECTION_MATRIX_CELL in Direction::DIRECTION_MATRIX() {
-            let sequence = (self.get_current_sequence(max_depth, i32::try_from(row).unwrap(), i32::try_from(col).unwrap(), &DIRECTION_MATRIX_CELL));
-            sequences.insert(DIRECTION_MATRIX_CELL, sequence.unwrap_or(String::new()));
+        for direction in &self.directions {
+            let sequence = self.get_current_sequence(max_depth, i32::try_from(row).unwrap(), i32::try_from(col).unwrap(), direction);
+            sequences.insert(*direction, sequence.unwrap_or_default());
             // sequences[DIRECTION_MATRIX_CELL.getIndex()] = sequence;
         }
 
@@ -117,6 +266,7 @@ impl Board {
         }
 
         let mut sequence = String::from("");
+        let mut open = false;
 
         for depth in 0..max_length {
             let row_direction = (direction.getRow() * depth) + row;
@@ -126,67 +276,91 @@ impl Board {
                 break;
             }
 
-            let c = self.board[row_direction as usize][col_direction as usize];
-            if (c == '*') {
+            if self.is_obstacle(row_direction as usize, col_direction as usize) {
                 break;
             }
 
+            let c = self.board[row_direction as usize][col_direction as usize];
+            open |= self.is_open(row_direction as usize, col_direction as usize);
+
             sequence.push(c);
         }
 
-        // FIXME if sequence is all existing chars, we should skit it
-        if !sequence.contains('?') {
+        // Sequence of letters already covered by words is skipped
+        if !open {
             return None;
         }
         return Some(sequence);
     }
 
     fn remove_sequence_from_board(&mut self, sequence: String, row: usize, col: usize, direction: Direction) {
-        for (depth, c) in sequence.char_indices() {
+        for (depth, _c) in sequence.chars().enumerate() {
             let row = i32::try_from(row).unwrap() + (direction.getRow() * i32::try_from(depth).unwrap());
             let col = i32::try_from(col).unwrap() + (direction.getCol() * i32::try_from(depth).unwrap());
 
-            if (row >= 0 && col >= 0) {
-                if Some(self.tracker) == self.contributions[row as usize][col as usize] {
-                    self.board[row as usize][col as usize] = '?';
-                    self.contributions[row as usize][col as usize] = None;
-                }
+            if row >= 0 && col >= 0 {
+                self.coverage[row as usize][col as usize] -= 1;
+            }
+            if row >= 0 && col >= 0 && Some(self.tracker) == self.contributions[row as usize][col as usize] {
+                self.board[row as usize][col as usize] = '?';
+                self.contributions[row as usize][col as usize] = None;
             }
         }
     }
 
     fn put_sequence_on_board(&mut self, sequence: String, row: usize, col: usize, direction: Direction) {
-        for (depth, c) in sequence.char_indices() {
+        for (depth, c) in sequence.chars().enumerate() {
             let row = i32::try_from(row).unwrap() + (direction.getRow() * i32::try_from(depth).unwrap());
             let col = i32::try_from(col).unwrap() + (direction.getCol() * i32::try_from(depth).unwrap());
 
-            if (row >= 0 && col >= 0) {
+            if row >= 0 && col >= 0 {
                 self.board[row as usize][col as usize] = c;
                 self.contributions[row as usize][col as usize].get_or_insert(self.tracker);
+                self.coverage[row as usize][col as usize] += 1;
             }
         }
     }
 }
 
+// Each cell is printed with index of the word which put the letter on board
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                match self.contributions[row][col] {
+                    Some(FIXED) => write!(f, "{}-fx\t", self.board[row][col])?,
+                    Some(FILLER) => write!(f, "{}-fl\t", self.board[row][col])?,
+                    Some(SOLUTION) => write!(f, "{}-sl\t", self.board[row][col])?,
+                    contribution => write!(f, "{}-{:0>2}\t", self.board[row][col], contribution.unwrap_or(99))?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    #[should_panic(expected = "too big")]
     fn test_big_board() {
         let rows = 100;
         let cols = 100;
         let board = Board::new(rows, cols);
+
+        assert!(board.is_ok());
     }
 
     #[test]
-    #[should_panic(expected = "too small")]
     fn test_empty_board() {
         let rows = 0;
         let cols = 0;
         let board = Board::new(rows, cols);
+
+        assert_eq!(board.err(), Some(CrosswordoError::BoardTooSmall { rows, cols }));
     }
 
 
@@ -195,21 +369,123 @@ mod tests {
         let rows = 8;
         let cols = 4;
         let board = Board::new(rows, cols);
+
+        assert!(board.is_ok());
     }
 
     #[test]
     fn test_content_board() {
         let rows = 8;
         let cols = 4;
-        let board = Board::new(rows, cols);
+        let board = Board::new(rows, cols).unwrap();
         assert_eq!(board.board, vec![vec!['?'; cols]; rows])
     }
 
+    #[test]
+    fn test_count_empty_cells() {
+        let mut board = Board::new(4, 4).unwrap();
+        board.put_word_on_board(&Word::new(String::from("cold"), Direction::SOUTH(), (0, 1)));
+
+        assert_eq!(board.count_empty_cells(), 12);
+    }
+
+    #[test]
+    fn test_template_letters_need_word() {
+        let template = Template::parse("??b?\n????\n???#\n????").unwrap();
+        let mut board = Board::new(4, 4).unwrap().with_template(&template);
+        assert_eq!(board.count_empty_cells(), 15);
+
+        let word = Word::new(String::from("cube"), Direction::EAST(), (0, 0));
+        board.put_word_on_board(&word);
+        assert_eq!(board.count_empty_cells(), 11);
+
+        board.remove_word_from_board(&word);
+        assert_eq!(board.count_empty_cells(), 15);
+        assert!(board.get_current_sequence(4, 0, 2, &Direction::EAST()).is_some());
+    }
+
+    #[test]
+    fn test_template_letters_are_kept() {
+        let template = Template::parse("??b?\n????\n??#?\n????").unwrap();
+        let mut board = Board::new(4, 4).unwrap().with_template(&template);
+
+        let word = Word::new(String::from("cube"), Direction::EAST(), (0, 0));
+        board.put_word_on_board(&word);
+        board.remove_word_from_board(&word);
+
+        assert_eq!(board.board[0], vec!['?', '?', 'b', '?']);
+        assert_eq!(board.get_sequences_from_position(0, 2).unwrap()[&Direction::SOUTH()], "b?");
+        assert!(board.get_sequences_from_position(2, 2).is_none());
+    }
+
+    #[test]
+    fn test_solution_letters() {
+        let mut board = Board::new(4, 4).unwrap();
+        let mut solution = Solution::new("ab", 4, 4).unwrap();
+        solution.locations.insert(1, 'a');
+        solution.locations.insert(8, 'b');
+        board.put_solution_on_board(&solution);
+
+        assert_eq!(board.board[0], vec!['?', 'a', '?', '?']);
+        assert!(board.is_solution_cell(2, 0));
+        assert!(board.get_sequences_from_position(0, 1).is_none());
+        assert_eq!(board.get_sequences_from_position(0, 0).unwrap()[&Direction::EAST()], "?");
+        // Solution letter cannot be shared with a word even when it matches
+        assert!(!board.find_placements("bad").contains(&Word::new(String::from("bad"), Direction::SOUTH(), (2, 0))));
+    }
+
+    #[test]
+    fn test_crossings() {
+        let word = Word::new(String::from("cold"), Direction::EAST(), (0, 0));
+        let mut board = Board::new(4, 4).unwrap();
+        board.put_word_on_board(&word);
+        assert_eq!(board.get_sequences_from_position(0, 0).unwrap()[&Direction::SOUTH()], "c???");
+        assert_eq!(board.count_overlaps(&Word::new(String::from("clod"), Direction::SOUTH(), (0, 0))), 1);
+
+        let mut board = Board::new(4, 4).unwrap().with_crossings(false);
+        board.put_word_on_board(&word);
+        assert!(board.get_sequences_from_position(0, 0).is_none());
+        assert_eq!(board.get_sequences_from_position(1, 0).unwrap()[&Direction::NORTH()], "?");
+        assert!(board.find_placements("clod").iter().all(|placement| placement.coords.0 > 0));
+    }
+
+    #[test]
+    fn test_solution_crossings() {
+        let mut solution = Solution::new("a", 4, 4).unwrap();
+        solution.locations.insert(5, 'a');
+        let mut board = Board::new(4, 4).unwrap().with_strict_solution(false);
+        board.put_solution_on_board(&solution);
+
+        assert_eq!(board.get_sequences_from_position(1, 0).unwrap()[&Direction::EAST()], "?a??");
+        assert!(board.find_placements("bat").contains(&Word::new(String::from("bat"), Direction::EAST(), (1, 0))));
+    }
+
+    #[test]
+    fn test_find_placements() {
+        let mut board = Board::new(4, 4).unwrap().with_directions(&[Direction::EAST(), Direction::SOUTH()]);
+        board.put_word_on_board(&Word::new(String::from("cold"), Direction::EAST(), (0, 0)));
+
+        let placements = board.find_placements("oak");
+
+        assert_eq!(placements.len(), 3 * 2 + 1 + 4);
+        assert!(placements.contains(&Word::new(String::from("oak"), Direction::SOUTH(), (0, 1))));
+        assert!(!placements.iter().any(|word| word.coords.0 == 0 && word.direction == Direction::EAST()));
+    }
+
+    #[test]
+    fn test_sequences_in_directions() {
+        let board = Board::new(4, 4).unwrap().with_directions(&[Direction::EAST(), Direction::SOUTH()]);
+        let sequences = board.get_sequences_from_position(0, 0).unwrap();
+
+        assert_eq!(sequences.keys().cloned().collect::<Vec<Direction>>(), vec![Direction::EAST(), Direction::SOUTH()]);
+        assert_eq!(sequences[&Direction::EAST()], "????");
+    }
+
     #[test]
     fn test_items_board() {
         let rows = 8;
         let cols = 4;
-        let board = Board::new(rows, cols);
+        let board = Board::new(rows, cols).unwrap();
 
         assert_eq!(board.board.len(), rows);
         for row in board.board {
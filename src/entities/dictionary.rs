@@ -0,0 +1,45 @@
+use std::collections::HashSet;
+use crate::entities::pattern::Pattern;
+
+// Word lookup used by the generator, it is implemented by the Trie and the compact PackedTrie
+pub trait Dictionary {
+    // Words matching the sequence where '?' stands for any character
+    fn search(&self, sequence: &str) -> HashSet<String> {
+        self.search_iter(sequence).collect()
+    }
+
+    // Matches of search found one by one while iterating
+    fn search_iter<'a>(&'a self, sequence: &str) -> Box<dyn Iterator<Item = String> + 'a> {
+        self.matches(&Pattern::sequence(sequence))
+    }
+
+    // Number of matches of search without collecting them
+    fn count(&self, sequence: &str) -> usize;
+
+    // Words matching a pattern with character classes, runs and prefixes, found one by one while iterating
+    fn matches<'a>(&'a self, pattern: &Pattern) -> Box<dyn Iterator<Item = String> + 'a>;
+
+    fn count_matches(&self, pattern: &Pattern) -> usize;
+
+    // Exact lookup, the sequence is not treated as a pattern
+    fn contains(&self, word: &str) -> bool;
+
+    // Frequency or score loaded with the word, higher means more common
+    fn frequency(&self, word: &str) -> Option<f32>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// Data structure the dictionary words are loaded into
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DictionaryBackend {
+    // Node per character with a map of children, fast to build
+    #[default]
+    Trie,
+    // Nodes packed into flat arrays, uses a fraction of the memory of Trie
+    Packed,
+}
@@ -1,4 +1,7 @@
-#[derive(Clone, Copy, PartialOrd, PartialEq, Hash, Eq, Ord)]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+
+#[derive(Clone, Copy, Debug, PartialOrd, PartialEq, Hash, Eq, Ord)]
 pub enum Direction {
     __NORTHWEST{row: i32, col: i32},
     __NORTH{row: i32, col: i32},
@@ -29,31 +32,45 @@ impl Direction {
         ]
     }
 
+    // All eight directions in which a word can be read
+    pub fn ALL() -> [Direction; 8] {
+        return [
+            Self::NORTHWEST(), Self::NORTH(), Self::NORTHEAST(),
+            Self::WEST(), Self::EAST(),
+            Self::SOUTHWEST(), Self::SOUTH(), Self::SOUTHEAST(),
+        ]
+    }
+
+    // Directions without backwards words, each word is read from left to right or from top to bottom
+    pub fn FORWARD() -> [Direction; 4] {
+        return [Self::NORTHEAST(), Self::EAST(), Self::SOUTH(), Self::SOUTHEAST()]
+    }
+
     pub fn getRow(&self) -> i32 {
         return match self {
-            Direction::__NORTHWEST { row, col } => { *row }
-            Direction::__NORTH { row, col } => { *row }
-            Direction::__NORTHEAST { row, col } => { *row }
-            Direction::__WEST { row, col } => { *row }
-            Direction::__CENTER { row, col } => { *row }
-            Direction::__EAST { row, col } => { *row }
-            Direction::__SOUTHWEST { row, col } => { *row }
-            Direction::__SOUTH { row, col } => { *row }
-            Direction::__SOUTHEAST { row, col } => { *row }
+            Direction::__NORTHWEST { row, .. } => { *row }
+            Direction::__NORTH { row, .. } => { *row }
+            Direction::__NORTHEAST { row, .. } => { *row }
+            Direction::__WEST { row, .. } => { *row }
+            Direction::__CENTER { row, .. } => { *row }
+            Direction::__EAST { row, .. } => { *row }
+            Direction::__SOUTHWEST { row, .. } => { *row }
+            Direction::__SOUTH { row, .. } => { *row }
+            Direction::__SOUTHEAST { row, .. } => { *row }
         }
     }
 
     pub fn getCol(&self) -> i32 {
         return match self {
-            Direction::__NORTHWEST { row, col } => { *col }
-            Direction::__NORTH { row, col } => { *col }
-            Direction::__NORTHEAST { row, col } => { *col }
-            Direction::__WEST { row, col } => { *col }
-            Direction::__CENTER { row, col } => { *col }
-            Direction::__EAST { row, col } => { *col }
-            Direction::__SOUTHWEST { row, col } => { *col }
-            Direction::__SOUTH { row, col } => { *col }
-            Direction::__SOUTHEAST { row, col } => { *col }
+            Direction::__NORTHWEST { col, .. } => { *col }
+            Direction::__NORTH { col, .. } => { *col }
+            Direction::__NORTHEAST { col, .. } => { *col }
+            Direction::__WEST { col, .. } => { *col }
+            Direction::__CENTER { col, .. } => { *col }
+            Direction::__EAST { col, .. } => { *col }
+            Direction::__SOUTHWEST { col, .. } => { *col }
+            Direction::__SOUTH { col, .. } => { *col }
+            Direction::__SOUTHEAST { col, .. } => { *col }
         }
     }
 
@@ -71,4 +88,62 @@ impl Direction {
         }
     }
 
-}
\ No newline at end of file
+    pub fn getName(&self) -> &'static str {
+        return match self {
+            Direction::__NORTHWEST { .. } =>    {"NORTHWEST"}
+            Direction::__NORTH { .. } =>        {"NORTH"}
+            Direction::__NORTHEAST { .. } =>    {"NORTHEAST"}
+            Direction::__WEST { .. } =>         {"WEST"}
+            Direction::__CENTER { .. } =>       {"CENTER"}
+            Direction::__EAST { .. } =>         {"EAST"}
+            Direction::__SOUTHWEST { .. } =>    {"SOUTHWEST"}
+            Direction::__SOUTH { .. } =>        {"SOUTH"}
+            Direction::__SOUTHEAST { .. } =>    {"SOUTHEAST"}
+        }
+    }
+
+    pub fn fromName(name: &str) -> Option<Direction> {
+        Self::DIRECTION_MATRIX()
+            .into_iter()
+            .find(|direction| direction.getName().eq_ignore_ascii_case(name))
+    }
+
+}
+// Directions are stored by their names so exported puzzles stay readable
+impl Serialize for Direction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.getName())
+    }
+}
+
+impl<'de> Deserialize<'de> for Direction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Direction::fromName(&name).ok_or_else(|| D::Error::custom(format!("unknown direction '{}'", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Direction::fromName("east"), Some(Direction::EAST()));
+        assert_eq!(Direction::fromName("SouthWest"), Some(Direction::SOUTHWEST()));
+        assert_eq!(Direction::fromName("up"), None);
+    }
+
+    #[test]
+    fn test_all_without_center() {
+        assert!(!Direction::ALL().contains(&Direction::CENTER()));
+        assert_eq!(Direction::ALL().len(), 8);
+    }
+
+    #[test]
+    fn test_serialize_by_name() {
+        assert_eq!(serde_json::to_string(&Direction::NORTHEAST()).unwrap(), "\"NORTHEAST\"");
+        assert_eq!(serde_json::from_str::<Direction>("\"SOUTH\"").unwrap(), Direction::SOUTH());
+        assert!(serde_json::from_str::<Direction>("\"UP\"").is_err());
+    }
+}
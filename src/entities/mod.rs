@@ -1,5 +1,11 @@
 pub mod trie;
+pub mod dictionary;
+pub mod packed_trie;
+pub mod pattern;
 pub mod board;
 pub mod solution;
 pub mod direction;
 pub mod word;
+pub mod puzzle;
+pub mod template;
+pub mod placement;
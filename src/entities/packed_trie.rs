@@ -0,0 +1,543 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::ops::{Deref, Range};
+use std::path::Path;
+use memmap2::Mmap;
+use crate::entities::dictionary::Dictionary;
+use crate::entities::pattern::Pattern;
+use crate::error::CrosswordoError;
+use crate::MAX;
+
+// Binary image of the trie, the version is raised whenever the layout changes so old files are rejected
+const MAGIC: &[u8; 8] = b"CRWDDICT";
+pub const IMAGE_VERSION: u32 = 2;
+// Magic, version, number of nodes, number of words and a reserved field
+const HEADER: usize = 24;
+// Label, first child, child count and frequency (NaN when missing) as little endian 32 bit values followed
+// by a terminal byte
+const NODE_SIZE: usize = 17;
+
+// Bytes of the image, built in memory or mapped from a compiled dictionary file
+enum Image {
+    Owned(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl Deref for Image {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Image::Owned(bytes) => bytes,
+            Image::Mapped(map) => map,
+        }
+    }
+}
+
+// Trie stored in flat arrays indexed by node, the root is node 0. Children of a node are stored next to each
+// other sorted by label so a literal character is found by binary search, and words are not stored at all
+// but rebuilt from labels on the way down. The arrays are kept in the same bytes as they are written to
+// a compiled dictionary, so a compiled file is searched directly from the memory map without being parsed
+pub struct PackedTrie {
+    image: Image,
+    nodes: usize,
+    items: usize,
+}
+
+impl PackedTrie {
+    pub fn new(words: &[String]) -> Result<Self, CrosswordoError> {
+        Self::weighted(words, &HashMap::new())
+    }
+
+    // Words keep their frequency on the terminal node
+    pub fn weighted(words: &[String], frequencies: &HashMap<String, f32>) -> Result<Self, CrosswordoError> {
+        if let Some((word, _)) = frequencies.iter().find(|(_, frequency)| !frequency.is_finite() || **frequency < 0.0) {
+            return Err(CrosswordoError::InvalidFrequency(word.clone()));
+        }
+
+        let mut words: Vec<Vec<char>> = words.iter().map(|word| word.chars().collect()).collect();
+        words.sort();
+        words.dedup();
+
+        if let Some(word) = words.iter().find(|word| word.len() < MAX) {
+            return Err(CrosswordoError::WordTooShort(word.iter().collect()));
+        }
+
+        let mut labels: Vec<char> = vec!['\0'];
+        let mut first_child: Vec<u32> = vec![0];
+        let mut child_count: Vec<u32> = vec![0];
+        let mut terminal: Vec<bool> = vec![false];
+        let mut frequency: Vec<f32> = vec![f32::NAN];
+
+        // Nodes are created breadth first so all children of a node end up next to each other. Each node owns
+        // the range of sorted words sharing its prefix
+        let mut queue: VecDeque<(usize, usize, usize, usize)> = VecDeque::from([(0, 0, words.len(), 0)]);
+        while let Some((node, mut start, end, depth)) = queue.pop_front() {
+            if start < end && words[start].len() == depth {
+                terminal[node] = true;
+                frequency[node] = frequencies.get(&words[start].iter().collect::<String>()).copied().unwrap_or(f32::NAN);
+                start += 1;
+            }
+
+            first_child[node] = labels.len() as u32;
+            while start < end {
+                let label = words[start][depth];
+                let next = start + words[start..end].iter().take_while(|word| word[depth] == label).count();
+
+                queue.push_back((labels.len(), start, next, depth + 1));
+                labels.push(label);
+                first_child.push(0);
+                child_count.push(0);
+                terminal.push(false);
+                frequency.push(f32::NAN);
+
+                start = next;
+            }
+            child_count[node] = labels.len() as u32 - first_child[node];
+        }
+
+        let nodes = labels.len();
+        let mut image: Vec<u8> = Vec::with_capacity(HEADER + nodes * NODE_SIZE);
+        image.extend_from_slice(MAGIC);
+        for value in [IMAGE_VERSION, nodes as u32, words.len() as u32, 0] {
+            image.extend_from_slice(&value.to_le_bytes());
+        }
+        let frequency = frequency.iter().map(|frequency| frequency.to_bits());
+        for value in labels.iter().map(|label| *label as u32).chain(first_child).chain(child_count).chain(frequency) {
+            image.extend_from_slice(&value.to_le_bytes());
+        }
+        image.extend(terminal.iter().map(|terminal| u8::from(*terminal)));
+
+        Ok(PackedTrie { image: Image::Owned(image), nodes, items: words.len() })
+    }
+
+    // Reads a compiled dictionary, the whole file is checked once and then used in place
+    pub fn from_image(bytes: Vec<u8>) -> Result<Self, CrosswordoError> {
+        Self::validate(Image::Owned(bytes))
+    }
+
+    // Maps a compiled dictionary into memory, pages are loaded by the system as the search visits them
+    pub fn open(path: &Path) -> Result<Self, CrosswordoError> {
+        let file = File::open(path)
+            .map_err(|error| CrosswordoError::InvalidDictionary(format!("{}: {}", path.display(), error)))?;
+        // SAFETY: compiled dictionaries are written once and never modified while they are in use
+        let map = unsafe { Mmap::map(&file) }
+            .map_err(|error| CrosswordoError::InvalidDictionary(format!("{}: {}", path.display(), error)))?;
+
+        Self::validate(Image::Mapped(map))
+    }
+
+    // Bytes written to a compiled dictionary file
+    pub fn image(&self) -> &[u8] {
+        &self.image
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), CrosswordoError> {
+        std::fs::write(path, self.image())
+            .map_err(|error| CrosswordoError::InvalidDictionary(format!("{}: {}", path.display(), error)))
+    }
+
+    fn validate(image: Image) -> Result<Self, CrosswordoError> {
+        if image.len() < HEADER || &image[..MAGIC.len()] != MAGIC {
+            return Err(CrosswordoError::InvalidDictionary(String::from("not a compiled dictionary")));
+        }
+
+        let header = |idx: usize| u32::from_le_bytes(image[8 + idx * 4..12 + idx * 4].try_into().unwrap());
+        if header(0) != IMAGE_VERSION {
+            return Err(CrosswordoError::InvalidDictionary(format!("version {} is not supported, expected {}", header(0), IMAGE_VERSION)));
+        }
+
+        let (nodes, items) = (header(1) as usize, header(2) as usize);
+        if nodes == 0 || image.len() != HEADER + nodes * NODE_SIZE {
+            return Err(CrosswordoError::InvalidDictionary(String::from("image is truncated")));
+        }
+
+        let trie = PackedTrie { image, nodes, items };
+        let mut terminals = 0;
+        for node in 0..nodes {
+            let children = trie.first_child(node) as usize + trie.child_count(node) as usize;
+            // Children always follow their parent, a range pointing back would make the traversal loop forever
+            let backwards = trie.child_count(node) > 0 && trie.first_child(node) as usize <= node;
+            let frequency = f32::from_bits(trie.value(3, node));
+            if char::from_u32(trie.label(node)).is_none() || children > nodes || backwards || trie.image[trie.terminal_offset() + node] > 1
+                || frequency < 0.0 || frequency.is_infinite() {
+                return Err(CrosswordoError::InvalidDictionary(format!("node {} is corrupted", node)));
+            }
+
+            // Children are found by binary search over their labels
+            if (trie.first_child(node) as usize + 1..children).any(|child| trie.label(child - 1) >= trie.label(child)) {
+                return Err(CrosswordoError::InvalidDictionary(format!("children of node {} are not sorted", node)));
+            }
+            terminals += usize::from(trie.terminal(node));
+        }
+
+        if terminals != items {
+            return Err(CrosswordoError::InvalidDictionary(format!("{} words stored but {} expected", terminals, items)));
+        }
+
+        Ok(trie)
+    }
+
+    // Number of nodes including the root
+    pub fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    // Every word of the dictionary in alphabetical order
+    pub fn words(&self) -> PackedSearch<'_> {
+        self.matches(&Pattern::any())
+    }
+
+    fn value(&self, array: usize, node: usize) -> u32 {
+        let offset = HEADER + (array * self.nodes + node) * 4;
+        u32::from_le_bytes(self.image[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn label(&self, node: usize) -> u32 {
+        self.value(0, node)
+    }
+
+    fn first_child(&self, node: usize) -> u32 {
+        self.value(1, node)
+    }
+
+    fn child_count(&self, node: usize) -> u32 {
+        self.value(2, node)
+    }
+
+    fn terminal_offset(&self) -> usize {
+        HEADER + self.nodes * 16
+    }
+
+    fn terminal(&self, node: usize) -> bool {
+        self.image[self.terminal_offset() + node] == 1
+    }
+
+    // Labels are checked when the image is read
+    fn character(&self, node: usize) -> char {
+        char::from_u32(self.label(node)).unwrap_or_default()
+    }
+
+    fn children(&self, node: usize) -> Range<usize> {
+        let first = self.first_child(node) as usize;
+        first..first + self.child_count(node) as usize
+    }
+
+    fn child(&self, node: usize, label: char) -> Option<usize> {
+        let Range { mut start, mut end } = self.children(node);
+        while start < end {
+            let middle = start + (end - start) / 2;
+            match self.label(middle).cmp(&(label as u32)) {
+                std::cmp::Ordering::Equal => return Some(middle),
+                std::cmp::Ordering::Less => start = middle + 1,
+                std::cmp::Ordering::Greater => end = middle,
+            }
+        }
+
+        None
+    }
+
+    // Lazy form of search, words are rebuilt from labels of the visited nodes
+    pub fn search_iter(&self, sequence: &str) -> PackedSearch<'_> {
+        self.matches(&Pattern::sequence(sequence))
+    }
+
+    pub fn matches(&self, pattern: &Pattern) -> PackedSearch<'_> {
+        PackedSearch {
+            trie: self,
+            stack: vec![(0, 0, pattern.start())],
+            pattern: pattern.clone(),
+            prefix: String::new(),
+        }
+    }
+
+    pub fn count_matches(&self, pattern: &Pattern) -> usize {
+        self.count_matches_from(0, pattern, 0, pattern.start())
+    }
+
+    fn count_matches_from(&self, node: usize, pattern: &Pattern, depth: usize, states: u128) -> usize {
+        let mut count = usize::from(self.terminal(node) && pattern.accepts(states, depth));
+        if !pattern.continues(states, depth) {
+            return count;
+        }
+
+        if let Some(c) = pattern.literal(states, depth) {
+            if let Some(child) = self.child(node, c) {
+                count += self.count_matches_from(child, pattern, depth + 1, pattern.step(states, depth, c));
+            }
+            return count;
+        }
+
+        for child in self.children(node) {
+            let next_states = pattern.step(states, depth, self.character(child));
+            if next_states != 0 {
+                count += self.count_matches_from(child, pattern, depth + 1, next_states);
+            }
+        }
+
+        count
+    }
+
+    pub fn count(&self, sequence: &str) -> usize {
+        self.count_from(0, sequence)
+    }
+
+    fn count_from(&self, node: usize, sequence: &str) -> usize {
+        let Some(c) = sequence.chars().next() else {
+            return usize::from(self.terminal(node));
+        };
+
+        let rest = &sequence[c.len_utf8()..];
+        if c == '?' {
+            return self.children(node).map(|child| self.count_from(child, rest)).sum();
+        }
+
+        self.child(node, c).map_or(0, |child| self.count_from(child, rest))
+    }
+}
+
+// Depth first traversal of nodes matching the pattern, prefix holds the labels on the path to the current node
+pub struct PackedSearch<'a> {
+    trie: &'a PackedTrie,
+    pattern: Pattern,
+    stack: Vec<(usize, usize, u128)>,
+    prefix: String,
+}
+
+impl Iterator for PackedSearch<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while let Some((node, depth, states)) = self.stack.pop() {
+            // Siblings share the prefix of their parent, labels of a previously visited subtree are dropped
+            if depth > 0 {
+                let parent_length: usize = self.prefix.chars().take(depth - 1).map(char::len_utf8).sum();
+                self.prefix.truncate(parent_length);
+                self.prefix.push(self.trie.character(node));
+            }
+
+            // Children are pushed in reverse so words come out sorted
+            if self.pattern.continues(states, depth) {
+                if let Some(c) = self.pattern.literal(states, depth) {
+                    if let Some(child) = self.trie.child(node, c) {
+                        self.stack.push((child, depth + 1, self.pattern.step(states, depth, c)));
+                    }
+                } else {
+                    for child in self.trie.children(node).rev() {
+                        let next_states = self.pattern.step(states, depth, self.trie.character(child));
+                        if next_states != 0 {
+                            self.stack.push((child, depth + 1, next_states));
+                        }
+                    }
+                }
+            }
+
+            if self.trie.terminal(node) && self.pattern.accepts(states, depth) {
+                return Some(self.prefix.clone());
+            }
+        }
+
+        None
+    }
+}
+
+impl Dictionary for PackedTrie {
+    fn count(&self, sequence: &str) -> usize {
+        PackedTrie::count(self, sequence)
+    }
+
+    fn matches<'a>(&'a self, pattern: &Pattern) -> Box<dyn Iterator<Item = String> + 'a> {
+        Box::new(PackedTrie::matches(self, pattern))
+    }
+
+    fn count_matches(&self, pattern: &Pattern) -> usize {
+        PackedTrie::count_matches(self, pattern)
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        let mut node = 0;
+        for c in word.chars() {
+            match self.child(node, c) {
+                Some(child) => node = child,
+                None => return false,
+            }
+        }
+
+        self.terminal(node)
+    }
+
+    fn frequency(&self, word: &str) -> Option<f32> {
+        let mut node = 0;
+        for c in word.chars() {
+            node = self.child(node, c)?;
+        }
+
+        let frequency = f32::from_bits(self.value(3, node));
+        (self.terminal(node) && !frequency.is_nan()).then_some(frequency)
+    }
+
+    fn len(&self) -> usize {
+        self.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use crate::entities::trie::Trie;
+    use crate::test_utils::{trie, words};
+
+    #[test]
+    fn test_small_word() {
+        let trie = PackedTrie::new(&words(&["star", "ca"]));
+
+        assert_eq!(trie.err(), Some(CrosswordoError::WordTooShort(String::from("ca"))));
+    }
+
+    #[test]
+    fn test_structure() {
+        let trie = PackedTrie::new(&words(&["star", "start", "stop", "star", "top"])).unwrap();
+
+        assert_eq!(trie.len(), 4);
+        // root, s, t, a, r, t, o, p, t, o, p
+        assert_eq!(trie.nodes(), 11);
+        assert!(trie.contains("star"));
+        assert!(trie.contains("start"));
+        assert!(!trie.contains("sta"));
+        assert!(!trie.contains("stars"));
+    }
+
+    #[test]
+    fn test_image() {
+        let dictionary = words(&["star", "start", "stop", "šťava"]);
+        let trie = PackedTrie::new(&dictionary).unwrap();
+        let loaded = PackedTrie::from_image(trie.image().to_vec()).unwrap();
+
+        assert_eq!(loaded.len(), 4);
+        assert_eq!(loaded.nodes(), trie.nodes());
+        assert_eq!(loaded.words().collect::<Vec<String>>(), vec!["star", "start", "stop", "šťava"]);
+
+        let path = std::env::temp_dir().join(format!("crosswordo-{}.dict", std::process::id()));
+        trie.write(&path).unwrap();
+        let mapped = PackedTrie::open(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(Dictionary::search(&mapped.unwrap(), "st??"), HashSet::from([String::from("star"), String::from("stop")]));
+    }
+
+    #[test]
+    fn test_frequencies() {
+        let frequencies = HashMap::from([(String::from("star"), 7.5), (String::from("sta"), 1.0)]);
+        let trie = PackedTrie::weighted(&words(&["star", "start"]), &frequencies).unwrap();
+        let loaded = PackedTrie::from_image(trie.image().to_vec()).unwrap();
+
+        assert_eq!(loaded.frequency("star"), Some(7.5));
+        assert_eq!(loaded.frequency("start"), None);
+        assert_eq!(loaded.frequency("sta"), None);
+
+        let invalid = HashMap::from([(String::from("star"), -1.0)]);
+        assert_eq!(PackedTrie::weighted(&words(&["star"]), &invalid).err(), Some(CrosswordoError::InvalidFrequency(String::from("star"))));
+    }
+
+    #[test]
+    fn test_invalid_image() {
+        let image = PackedTrie::new(&words(&["star", "stop"])).unwrap().image().to_vec();
+
+        let mut version = image.clone();
+        version[8] = 0;
+        let truncated = image[..image.len() - 1].to_vec();
+        let mut corrupted = image.clone();
+        corrupted[HEADER + 4 * 4 + 2] = 0xff;
+
+        for image in [Vec::new(), b"not a dictionary at all".to_vec(), version, truncated, corrupted] {
+            assert!(matches!(PackedTrie::from_image(image), Err(CrosswordoError::InvalidDictionary(_))));
+        }
+        assert!(matches!(PackedTrie::open(Path::new("./res/missing.dict")), Err(CrosswordoError::InvalidDictionary(_))));
+    }
+
+    #[test]
+    fn test_cyclic_image() {
+        let trie = PackedTrie::new(&words(&["star", "stop"])).unwrap();
+        let mut image = trie.image().to_vec();
+        // Children of 't' point back to 's' and 't' itself, words would never end
+        let offset = HEADER + (trie.nodes() + 2) * 4;
+        image[offset..offset + 4].copy_from_slice(&1u32.to_le_bytes());
+
+        assert_eq!(PackedTrie::from_image(image).err(), Some(CrosswordoError::InvalidDictionary(String::from("node 2 is corrupted"))));
+    }
+
+    #[test]
+    fn test_unsorted_image() {
+        let trie = PackedTrie::new(&words(&["star", "stop"])).unwrap();
+        let mut image = trie.image().to_vec();
+        // Children of 't' are 'a' and 'o', 'z' before 'o' would hide "stop" from binary search
+        let offset = HEADER + 3 * 4;
+        image[offset..offset + 4].copy_from_slice(&u32::from('z').to_le_bytes());
+
+        assert_eq!(PackedTrie::from_image(image).err(), Some(CrosswordoError::InvalidDictionary(String::from("children of node 2 are not sorted"))));
+    }
+
+    #[test]
+    fn test_wrong_word_count() {
+        let mut image = PackedTrie::new(&words(&["star", "stop"])).unwrap().image().to_vec();
+        image[16..20].copy_from_slice(&3u32.to_le_bytes());
+
+        assert_eq!(PackedTrie::from_image(image).err(), Some(CrosswordoError::InvalidDictionary(String::from("2 words stored but 3 expected"))));
+    }
+
+    #[test]
+    fn test_same_results_as_trie() {
+        let dictionary = ["space", "place", "craze", "crate", "state", "plate", "blade", "blato", "star", "stare", "story", "šťava"];
+        let packed = PackedTrie::new(&words(&dictionary)).unwrap();
+        let trie = trie(&dictionary);
+
+        for pattern in ["??a?e", "space", "s????", "????", "?ť???", "water", "bl???", "?????"] {
+            assert_eq!(Dictionary::search(&packed, pattern), trie.search(pattern), "{}", pattern);
+            assert_eq!(packed.count(pattern), trie.count(pattern), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn test_same_matches_as_trie() {
+        let dictionary = ["space", "place", "craze", "crate", "state", "plate", "blade", "blato", "star", "stare", "story", "šťava"];
+        let packed = PackedTrie::new(&words(&dictionary)).unwrap();
+        let trie = trie(&dictionary);
+
+        for pattern in ["[bp]la*", "[^s]*e", "*a*", "st[aeiou]??", "š*"] {
+            let pattern = Pattern::parse(pattern).unwrap();
+            assert_eq!(Dictionary::matches(&packed, &pattern).collect::<HashSet<String>>(), Dictionary::matches(&trie, &pattern).collect::<HashSet<String>>(), "{}", pattern);
+            assert_eq!(packed.count_matches(&pattern), trie.count_matches(&pattern), "{}", pattern);
+        }
+
+        let prefixes = Pattern::sequence("stare?").with_prefixes(true).with_min_length(4);
+        assert_eq!(packed.matches(&prefixes).collect::<Vec<String>>(), vec!["star", "stare"]);
+    }
+
+    #[test]
+    fn test_same_length_as_trie() {
+        // Both count letters, "čo" takes 3 bytes but is as short as "co"
+        let mut trie = Trie::new();
+
+        assert_eq!(trie.insert("čo"), Err(CrosswordoError::WordTooShort(String::from("čo"))));
+        assert_eq!(PackedTrie::new(&words(&["šťava", "čo"])).err(), Some(CrosswordoError::WordTooShort(String::from("čo"))));
+        assert_eq!(trie.insert("šťava"), Ok(()));
+        assert!(PackedTrie::new(&words(&["šťava", "čaj"])).is_ok());
+    }
+
+    #[test]
+    fn test_same_len_as_trie() {
+        let dictionary = ["star", "stop", "star", "šťava", "stop"];
+        let trie = trie(&dictionary);
+
+        assert_eq!(trie.len(), 3);
+        assert_eq!(PackedTrie::new(&words(&dictionary)).unwrap().len(), trie.len());
+    }
+
+    #[test]
+    fn test_search_in_order() {
+        let trie = PackedTrie::new(&words(&["stare", "star", "stop", "spot", "slot"])).unwrap();
+
+        assert_eq!(trie.search_iter("s?o?").collect::<Vec<String>>(), vec!["slot", "spot", "stop"]);
+    }
+}
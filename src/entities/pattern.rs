@@ -0,0 +1,309 @@
+use std::fmt;
+use crate::error::CrosswordoError;
+
+// Patterns with runs track every reachable token in a bitmask, the last bit marks a complete match
+const MAX_TOKENS: usize = 127;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Token {
+    // Exactly this character
+    Char(char),
+    // '?' any single character
+    Any,
+    // '[aeiou]' one of the letters, '[^aeiou]' any other character
+    Class { letters: Vec<char>, negated: bool },
+    // '*' any run of characters, including an empty one
+    Run,
+}
+
+impl Token {
+    fn accepts(&self, c: char) -> bool {
+        match self {
+            Token::Char(letter) => *letter == c,
+            Token::Any | Token::Run => true,
+            Token::Class { letters, negated } => letters.contains(&c) != *negated,
+        }
+    }
+}
+
+// Search pattern for dictionaries, parsed from text such as "st[aeiou]*" or built from a board sequence.
+// Words matching only the beginning of the pattern are accepted with prefixes, so a word can end before a wall
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    tokens: Vec<Token>,
+    has_runs: bool,
+    prefixes: bool,
+    min_length: usize,
+    max_length: Option<usize>,
+}
+
+impl Pattern {
+    pub fn parse(pattern: &str) -> Result<Self, CrosswordoError> {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut chars = pattern.chars();
+
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '?' => Token::Any,
+                '*' => Token::Run,
+                '[' => {
+                    let mut letters: Vec<char> = Vec::new();
+                    let mut negated = false;
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        match c {
+                            ']' => {
+                                closed = true;
+                                break;
+                            }
+                            '^' if letters.is_empty() && !negated => negated = true,
+                            _ => letters.push(c),
+                        }
+                    }
+
+                    if !closed {
+                        return Err(CrosswordoError::InvalidPattern(format!("unclosed class in {}", pattern)));
+                    }
+                    if letters.is_empty() {
+                        return Err(CrosswordoError::InvalidPattern(format!("empty class in {}", pattern)));
+                    }
+                    Token::Class { letters, negated }
+                }
+                ']' => return Err(CrosswordoError::InvalidPattern(format!("unopened class in {}", pattern))),
+                _ => Token::Char(c),
+            };
+
+            // Consecutive runs match the same words as a single one
+            if !(token == Token::Run && tokens.last() == Some(&Token::Run)) {
+                tokens.push(token);
+            }
+        }
+
+        if tokens.len() > MAX_TOKENS {
+            return Err(CrosswordoError::InvalidPattern(format!("more than {} tokens in {}", MAX_TOKENS, pattern)));
+        }
+
+        Ok(Self::from_tokens(tokens))
+    }
+
+    // Sequence read from board, '?' is an empty cell and every other character is a letter
+    pub fn sequence(sequence: &str) -> Self {
+        let tokens: Vec<Token> = sequence
+            .chars()
+            .map(|c| if c == '?' { Token::Any } else { Token::Char(c) })
+            .collect();
+
+        Self::from_tokens(tokens)
+    }
+
+    // Matches every word of dictionary
+    pub fn any() -> Self {
+        Self::from_tokens(vec![Token::Run])
+    }
+
+    fn from_tokens(tokens: Vec<Token>) -> Self {
+        Pattern {
+            has_runs: tokens.contains(&Token::Run),
+            tokens,
+            prefixes: false,
+            min_length: 0,
+            max_length: None,
+        }
+    }
+
+    pub fn with_prefixes(mut self, prefixes: bool) -> Self {
+        self.prefixes = prefixes;
+        self
+    }
+
+    pub fn with_min_length(mut self, min_length: usize) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    // Matching walks the dictionary one character at a time keeping a set of states.
+    // Patterns with runs keep a bit per reachable token, the others are matched token by token at the current depth
+    // and keep a single bit telling whether the word still matches
+    pub fn start(&self) -> u128 {
+        if self.has_runs {
+            return self.closure(1);
+        }
+
+        1
+    }
+
+    pub fn step(&self, states: u128, depth: usize, c: char) -> u128 {
+        if !self.has_runs {
+            let matches = states != 0 && self.tokens.get(depth).is_some_and(|token| token.accepts(c));
+            return u128::from(matches);
+        }
+
+        let mut next: u128 = 0;
+        for (idx, token) in self.tokens.iter().enumerate() {
+            if states & (1 << idx) != 0 && token.accepts(c) {
+                next |= if *token == Token::Run { 1 << idx } else { 1 << (idx + 1) };
+            }
+        }
+
+        self.closure(next)
+    }
+
+    // A run can always be skipped, so the token after it is reachable as well
+    fn closure(&self, mut states: u128) -> u128 {
+        for (idx, token) in self.tokens.iter().enumerate() {
+            if *token == Token::Run && states & (1 << idx) != 0 {
+                states |= 1 << (idx + 1);
+            }
+        }
+
+        states
+    }
+
+    // Single character every matching word continues with, children can be looked up directly instead of visited
+    pub fn literal(&self, states: u128, depth: usize) -> Option<char> {
+        let idx = if self.has_runs {
+            if states.count_ones() != 1 {
+                return None;
+            }
+            states.trailing_zeros() as usize
+        } else {
+            depth
+        };
+
+        match self.tokens.get(idx) {
+            Some(Token::Char(c)) => Some(*c),
+            _ => None,
+        }
+    }
+
+    pub fn accepts(&self, states: u128, depth: usize) -> bool {
+        if states == 0 || depth < self.min_length || self.max_length.is_some_and(|max_length| depth > max_length) {
+            return false;
+        }
+
+        if self.prefixes {
+            return true;
+        }
+
+        if self.has_runs {
+            return states & (1 << self.tokens.len()) != 0;
+        }
+
+        depth == self.tokens.len()
+    }
+
+    // Whether longer words can still match
+    pub fn continues(&self, states: u128, depth: usize) -> bool {
+        if states == 0 || self.max_length.is_some_and(|max_length| depth >= max_length) {
+            return false;
+        }
+
+        self.has_runs || depth < self.tokens.len()
+    }
+
+    // Matches a single word the way a dictionary traversal does
+    pub fn is_match(&self, word: &str) -> bool {
+        let mut states = self.start();
+        let mut length = 0;
+        for (depth, c) in word.chars().enumerate() {
+            if !self.continues(states, depth) {
+                return false;
+            }
+            states = self.step(states, depth, c);
+            length += 1;
+        }
+
+        self.accepts(states, length)
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for token in &self.tokens {
+            match token {
+                Token::Char(c) => write!(f, "{}", c)?,
+                Token::Any => write!(f, "?")?,
+                Token::Run => write!(f, "*")?,
+                Token::Class { letters, negated } => {
+                    write!(f, "[{}{}]", if *negated { "^" } else { "" }, letters.iter().collect::<String>())?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &Pattern, word: &str) -> bool {
+        pattern.is_match(word)
+    }
+
+    #[test]
+    fn test_parse() {
+        let pattern = Pattern::parse("s[^aeiou]?**[ab]").unwrap();
+
+        assert_eq!(pattern.tokens(), &[
+            Token::Char('s'),
+            Token::Class { letters: vec!['a', 'e', 'i', 'o', 'u'], negated: true },
+            Token::Any,
+            Token::Run,
+            Token::Class { letters: vec!['a', 'b'], negated: false },
+        ]);
+        assert_eq!(pattern.to_string(), "s[^aeiou]?*[ab]");
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(matches!(Pattern::parse("st[ae"), Err(CrosswordoError::InvalidPattern(_))));
+        assert!(matches!(Pattern::parse("st[]"), Err(CrosswordoError::InvalidPattern(_))));
+        assert!(matches!(Pattern::parse("sta]"), Err(CrosswordoError::InvalidPattern(_))));
+    }
+
+    #[test]
+    fn test_classes() {
+        let vowel = Pattern::parse("st[aeiou]r").unwrap();
+        let consonant = Pattern::parse("s[^aeiou]?r").unwrap();
+
+        assert!(matches(&vowel, "star"));
+        assert!(!matches(&vowel, "stxr"));
+        assert!(matches(&consonant, "star"));
+        assert!(!matches(&consonant, "soar"));
+    }
+
+    #[test]
+    fn test_runs() {
+        let pattern = Pattern::parse("s*t*e").unwrap();
+
+        assert!(matches(&pattern, "state"));
+        assert!(matches(&pattern, "ste"));
+        assert!(!matches(&pattern, "star"));
+        assert!(!matches(&pattern, "states"));
+        assert!(!matches(&pattern.clone().with_max_length(5), "statute"));
+        assert!(matches(&pattern.with_max_length(7), "statute"));
+    }
+
+    #[test]
+    fn test_prefixes() {
+        let pattern = Pattern::sequence("st??e").with_prefixes(true).with_min_length(3);
+
+        assert!(matches(&pattern, "star"));
+        assert!(matches(&pattern, "stare"));
+        assert!(!matches(&pattern, "st"));
+        assert!(!matches(&pattern, "stares"));
+        assert!(!matches(&pattern, "stair"));
+        assert!(!matches(&Pattern::sequence("st??e"), "star"));
+    }
+}
@@ -0,0 +1,113 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+// How solution letters are spread on board, letters always follow the reading order of chosen cells
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlacementStrategy {
+    // Board is split into equal runs of cells in reading order with a letter in each of them
+    #[default]
+    Clusters,
+    // Any cell of board
+    Uniform,
+    // Cells of the second half of board, the first half is used only when these are not enough
+    End,
+    // Cells on the edges of board
+    Border,
+    // Consecutive cells of a spiral going clockwise from the top left corner inwards
+    Spiral,
+    // Consecutive cells in reading order at the bottom of board
+    Bottom,
+}
+
+impl PlacementStrategy {
+    // Positions (row * cols + col) in the order they are tried, cells which would leave no space for a word are
+    // skipped by solution. Clusters are placed by solution itself as each letter has its own run of cells
+    pub fn candidates<R: Rng>(&self, rows: usize, cols: usize, rng: &mut R) -> Vec<usize> {
+        let size = rows * cols;
+
+        match self {
+            PlacementStrategy::Clusters => unreachable!("clusters are placed run by run in Solution::place_in_clusters"),
+            PlacementStrategy::Uniform => {
+                let mut positions: Vec<usize> = (0..size).collect();
+                positions.shuffle(rng);
+                positions
+            }
+            PlacementStrategy::End => {
+                let mut positions: Vec<usize> = (size / 2..size).collect();
+                positions.shuffle(rng);
+                positions.extend((0..size / 2).rev());
+                positions
+            }
+            PlacementStrategy::Border => {
+                let (mut border, mut inner): (Vec<usize>, Vec<usize>) = (0..size).partition(|position| {
+                    let (row, col) = (position / cols, position % cols);
+                    row == 0 || col == 0 || row == rows - 1 || col == cols - 1
+                });
+                border.shuffle(rng);
+                inner.shuffle(rng);
+                border.extend(inner);
+                border
+            }
+            PlacementStrategy::Spiral => spiral(rows, cols),
+            PlacementStrategy::Bottom => (0..size).rev().collect(),
+        }
+    }
+}
+
+fn spiral(rows: usize, cols: usize) -> Vec<usize> {
+    let mut positions: Vec<usize> = Vec::new();
+    let (mut top, mut left) = (0, 0);
+    let (mut bottom, mut right) = (rows, cols);
+
+    while top < bottom && left < right {
+        positions.extend((left..right).map(|col| top * cols + col));
+        positions.extend((top + 1..bottom).map(|row| row * cols + right - 1));
+        if top + 1 < bottom {
+            positions.extend((left..right - 1).rev().map(|col| (bottom - 1) * cols + col));
+        }
+        if left + 1 < right {
+            positions.extend((top + 1..bottom - 1).rev().map(|row| row * cols + left));
+        }
+
+        top += 1;
+        left += 1;
+        bottom -= 1;
+        right -= 1;
+    }
+
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_spiral() {
+        let positions = PlacementStrategy::Spiral.candidates(3, 4, &mut ChaCha8Rng::seed_from_u64(1));
+
+        assert_eq!(positions, vec![0, 1, 2, 3, 7, 11, 10, 9, 8, 4, 5, 6]);
+        assert_eq!(spiral(4, 1), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_every_cell_once() {
+        for strategy in [PlacementStrategy::Uniform, PlacementStrategy::End, PlacementStrategy::Border, PlacementStrategy::Spiral, PlacementStrategy::Bottom] {
+            let mut positions = strategy.candidates(5, 6, &mut ChaCha8Rng::seed_from_u64(1));
+            positions.sort();
+
+            assert_eq!(positions, (0..30).collect::<Vec<usize>>(), "{:?}", strategy);
+        }
+    }
+
+    #[test]
+    fn test_border_first() {
+        let positions = PlacementStrategy::Border.candidates(4, 4, &mut ChaCha8Rng::seed_from_u64(1));
+
+        let mut inner = positions[12..].to_vec();
+        inner.sort();
+        assert_eq!(inner, vec![5, 6, 9, 10]);
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::entities::board::Board;
+use crate::entities::solution::{phrase_hint, Solution};
+use crate::entities::word::Word;
+use crate::error::CrosswordoError;
+
+// Finished puzzle detached from the generator, it holds everything necessary to present it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Puzzle {
+    pub rows: usize,
+    pub cols: usize,
+    #[serde(with = "grid_rows")]
+    pub grid: Vec<Vec<char>>,
+    // Words in the order they were placed on board
+    pub words: Vec<Word>,
+    // Letters of solution in the order they are placed on board
+    pub solution: String,
+    // Solution as written with word boundaries and punctuation
+    #[serde(default)]
+    pub phrase: String,
+    // Row and column of each solution character in reading order
+    pub solution_cells: Vec<(usize, usize)>,
+    // Cells holding random letters which are not part of any word, in reading order
+    #[serde(default)]
+    pub filler_cells: Vec<(usize, usize)>,
+    // Cells shared by more than one word or by a word and a solution letter
+    #[serde(default)]
+    pub overlaps: usize,
+    // Seed which reproduces this puzzle with the same dictionary and parameters
+    pub seed: u64,
+}
+
+impl Puzzle {
+    pub fn new(board: &Board, solution: &Solution, words: &[Word], seed: u64) -> Self {
+        let mut solution_cells: Vec<(usize, usize)> = solution.locations
+            .keys()
+            .map(|position| (position / board.cols, position % board.cols))
+            .collect();
+        solution_cells.sort();
+
+        let mut coverage: HashMap<(usize, usize), usize> = HashMap::new();
+        for cell in words.iter().flat_map(|word| word.cells()) {
+            *coverage.entry(cell).or_default() += 1;
+        }
+        let overlaps = coverage.iter().filter(|(cell, count)| **count > 1 || solution_cells.contains(cell)).count();
+
+        Puzzle {
+            rows: board.rows,
+            cols: board.cols,
+            grid: board.board.clone(),
+            words: words.to_vec(),
+            solution: solution.text().to_string(),
+            phrase: solution.phrase().to_string(),
+            solution_cells,
+            filler_cells: board.filler_cells(),
+            overlaps,
+            seed,
+        }
+    }
+
+    // Phrase of solution with letters replaced by blanks, e.g. "_ ___ _______"
+    pub fn hint(&self) -> String {
+        phrase_hint(self.answer())
+    }
+
+    // Puzzles exported without phrase have only the letters of solution
+    pub fn answer(&self) -> &str {
+        if self.phrase.is_empty() {
+            return &self.solution;
+        }
+
+        &self.phrase
+    }
+
+    pub fn to_json(&self) -> Result<String, CrosswordoError> {
+        serde_json::to_string_pretty(self).map_err(|error| CrosswordoError::Serialization(error.to_string()))
+    }
+
+    pub fn from_json(input: &str) -> Result<Puzzle, CrosswordoError> {
+        let puzzle: Puzzle = serde_json::from_str(input).map_err(|error| CrosswordoError::Serialization(error.to_string()))?;
+
+        if puzzle.grid.len() != puzzle.rows || puzzle.grid.iter().any(|row| row.len() != puzzle.cols) {
+            return Err(CrosswordoError::Serialization(format!("grid does not match the size {}x{}", puzzle.rows, puzzle.cols)));
+        }
+
+        Ok(puzzle)
+    }
+}
+
+// Each row of the grid is exported as a single string so the exported grid can be read at glance
+mod grid_rows {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(grid: &[Vec<char>], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(grid.iter().map(|row| row.iter().collect::<String>()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Vec<char>>, D::Error> {
+        let rows: Vec<String> = Vec::deserialize(deserializer)?;
+        Ok(rows.iter().map(|row| row.chars().collect()).collect())
+    }
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.grid {
+            let line: Vec<String> = row.iter().map(|c| c.to_string()).collect();
+            writeln!(f, "{}", line.join("\t"))?;
+        }
+
+        writeln!(f)?;
+        for word in &self.words {
+            writeln!(f, "{} at R{}C{} {}", word.word, word.coords.0, word.coords.1, word.direction.getName())?;
+        }
+
+        if !self.filler_cells.is_empty() {
+            let cells: Vec<String> = self.filler_cells.iter().map(|(row, col)| format!("R{}C{}", row, col)).collect();
+            writeln!(f, "filler: {}", cells.join(" "))?;
+        }
+        writeln!(f, "overlaps: {}", self.overlaps)?;
+        writeln!(f, "hint: {}", self.hint())?;
+        writeln!(f, "solution: {}", self.answer())?;
+        write!(f, "seed: {}", self.seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::direction::Direction;
+
+    #[test]
+    fn test_puzzle_from_board() {
+        let mut board = Board::new(4, 4).unwrap();
+        let mut solution = Solution::new("A b!", 4, 4).unwrap();
+        solution.locations.insert(15, 'b');
+        solution.locations.insert(5, 'a');
+        board.put_solution_on_board(&solution);
+
+        let words = vec![
+            Word::new(String::from("cold"), Direction::EAST(), (0, 0)),
+            Word::new(String::from("cats"), Direction::SOUTH(), (0, 0)),
+        ];
+        for word in &words {
+            board.put_word_on_board(word);
+        }
+
+        let puzzle = Puzzle::new(&board, &solution, &words, 42);
+
+        assert_eq!(puzzle.grid[0], vec!['c', 'o', 'l', 'd']);
+        assert_eq!(puzzle.grid[1][1], 'a');
+        assert_eq!(puzzle.words, words);
+        assert_eq!(puzzle.solution, "ab");
+        assert_eq!(puzzle.phrase, "A b!");
+        assert_eq!(puzzle.hint(), "_ _!");
+        assert_eq!(puzzle.solution_cells, vec![(1, 1), (3, 3)]);
+        assert!(puzzle.filler_cells.is_empty());
+        assert_eq!(puzzle.overlaps, 1);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut board = Board::new(4, 4).unwrap();
+        let mut solution = Solution::new("a", 4, 4).unwrap();
+        solution.locations.insert(5, 'a');
+        board.put_solution_on_board(&solution);
+
+        let words = vec![Word::new(String::from("cold"), Direction::SOUTH(), (0, 0))];
+        board.put_word_on_board(&words[0]);
+        board.put_filler(3, 3, 'x');
+        let puzzle = Puzzle::new(&board, &solution, &words, 7);
+
+        let json = puzzle.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["grid"][1], "oa??");
+        assert_eq!(value["words"][0]["direction"], "SOUTH");
+        assert_eq!(value["words"][0]["coords"], serde_json::json!([0, 0]));
+        assert_eq!(value["words"][0]["length"], 4);
+        assert_eq!(value["filler_cells"], serde_json::json!([[3, 3]]));
+        assert_eq!(Puzzle::from_json(&json).unwrap(), puzzle);
+    }
+
+    #[test]
+    fn test_json_size_mismatch() {
+        let json = r#"{"rows": 2, "cols": 2, "grid": ["ab"], "words": [], "solution": "", "solution_cells": [], "seed": 0}"#;
+
+        assert!(matches!(Puzzle::from_json(json), Err(CrosswordoError::Serialization(_))));
+    }
+
+    #[test]
+    fn test_json_without_phrase() {
+        let json = r#"{"rows": 1, "cols": 2, "grid": ["ab"], "words": [], "solution": "ab", "solution_cells": [], "seed": 0}"#;
+        let puzzle = Puzzle::from_json(json).unwrap();
+
+        assert_eq!(puzzle.answer(), "ab");
+        assert_eq!(puzzle.hint(), "__");
+    }
+}
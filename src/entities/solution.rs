@@ -1,56 +1,128 @@
-use std::cmp::{max, min};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use log::debug;
 use std::ops::Range;
-use std::vec::IntoIter;
-use rand::{random, Rng, thread_rng};
+use rand::Rng;
 use rand::seq::SliceRandom;
-use regex::Regex;
-use crate::entities::board::Board;
 use crate::entities::direction::Direction;
+use crate::entities::placement::PlacementStrategy;
+use crate::entities::template::Template;
+use crate::error::CrosswordoError;
 use crate::MAX;
 
 pub struct Solution {
     rows: usize, cols: usize,
     pub locations: HashMap<usize, char>,
-    processed: String
+    // Input as written with word boundaries and punctuation, only its letters are placed on board
+    phrase: String,
+    processed: String,
+    // Solution cell must leave space for a word in at least one of these directions
+    directions: Vec<Direction>,
+    // Positions taken by template letters or blocked cells, solution cannot be placed there
+    fixed: HashSet<usize>,
+    // Positions of blocked template cells, words cannot pass them
+    blocked: HashSet<usize>,
+    strategy: PlacementStrategy,
 }
 
 impl Solution {
-    pub fn new(input: &str, rows: usize, cols: usize) -> Solution {
+    pub fn new(input: &str, rows: usize, cols: usize) -> Result<Solution, CrosswordoError> {
         // The preprocessing of an input removes special characters, whitespaces and numeric characters
-        let max_length: usize = ((cols * rows) / 2);
+        let max_length: usize = (cols * rows) / 2;
         let output: String = input.to_lowercase().chars().filter(|c| c.is_alphabetic()).collect();
 
-        if output.len() == 0 {
-            panic!("Support for no solution is not available.");
+        if output.is_empty() {
+            return Err(CrosswordoError::EmptySolution);
         }
 
-        if output.len() > max_length {
-            panic!("The solution you provided is too long, please ensure you solution does not exceeds {}!", max_length);
+        // Letters are counted, accented letters take more than a byte
+        let length = output.chars().count();
+        if length > max_length {
+            return Err(CrosswordoError::SolutionTooLong { length, max_length });
         }
 
-        Solution {
+        Ok(Solution {
             rows, cols,
             locations: HashMap::new(),
-            processed: output
+            phrase: input.split_whitespace().collect::<Vec<&str>>().join(" "),
+            processed: output,
+            directions: Direction::ALL().to_vec(),
+            fixed: HashSet::new(),
+            blocked: HashSet::new(),
+            strategy: PlacementStrategy::default(),
+        })
+    }
+
+    pub fn with_directions(mut self, directions: &[Direction]) -> Self {
+        self.directions = directions.to_vec();
+        self
+    }
+
+    pub fn with_strategy(mut self, strategy: PlacementStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn with_template(mut self, template: &Template) -> Self {
+        for row in 0..template.rows() {
+            for col in 0..template.cols() {
+                let position = row * self.cols + col;
+                if template.is_fixed(row, col) {
+                    self.fixed.insert(position);
+                }
+                if template.is_blocked(row, col) {
+                    self.blocked.insert(position);
+                }
+            }
         }
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.processed
+    }
+
+    pub fn phrase(&self) -> &str {
+        &self.phrase
+    }
+
+    pub fn hint(&self) -> String {
+        phrase_hint(&self.phrase)
     }
 
-    pub fn calculate_constraints(&mut self) -> bool {
+    pub fn calculate_constraints<R: Rng>(&mut self, rng: &mut R) -> bool {
+        let placed = match self.strategy {
+            PlacementStrategy::Clusters => self.place_in_clusters(rng),
+            strategy => {
+                let candidates = strategy.candidates(self.rows, self.cols, rng);
+                self.place_in_order(candidates)
+            }
+        };
+
+        if !placed {
+            debug!("placement of solution '{}' seems invalid", self.processed);
+            return false;
+        }
+
+        debug!("placement of solution '{}' seems valid", self.processed);
+        return true;
+    }
 
+    fn place_in_clusters<R: Rng>(&mut self, rng: &mut R) -> bool {
         // First we devide the whole board into the clusters based on solution length
         let mut clusters: Vec<Vec<(usize, usize)>> = Vec::new();
-        let cluster_size: usize = (self.cols * self.rows) / self.processed.len();
-        let cluster_remainder = (self.rows * self.cols) % self.processed.len();
+        let length = self.processed.chars().count();
+        let cluster_size: usize = (self.cols * self.rows) / length;
+        let cluster_remainder = (self.rows * self.cols) % length;
 
-        for clusterNumber in 0..self.processed.len() {
-            let remainder = (i32::try_from(clusterNumber + cluster_remainder).unwrap() - i32::try_from(self.processed.len()).unwrap() + 1);
+        for cluster_number in 0..length {
+            // Last clusters take one more cell each so the remainder of board is covered
+            let remainder = (cluster_number + cluster_remainder + 1).saturating_sub(length);
 
-            let cluster_positions: Range<usize> = if (remainder <= 0) {
-                 (clusterNumber * cluster_size) .. ((clusterNumber + 1) * cluster_size)
+            let cluster_positions: Range<usize> = if remainder == 0 {
+                 (cluster_number * cluster_size) .. ((cluster_number + 1) * cluster_size)
             } else {
-                let temp_usize_remainder = usize::try_from(remainder).unwrap_or(0);
-                (clusterNumber * cluster_size + temp_usize_remainder - 1) .. ((clusterNumber + 1) * cluster_size + temp_usize_remainder)
+                (cluster_number * cluster_size + remainder - 1) .. ((cluster_number + 1) * cluster_size + remainder)
             };
 
             clusters.push(
@@ -66,26 +138,60 @@ impl Solution {
         // for each cluster we are trying to find ideal position
         for cluster in clusters {
             // If there is at least once a situation where there is nowhere to put the solution it cannot be constructed
-            if !self.place_in_cluster(cluster) {
-                println!("placement seems invalid");
+            if !self.place_in_cluster(cluster, rng) {
                 return false;
             }
         }
 
-        println!("placement seems valid");
+        return true;
+    }
+
+    // Takes candidate cells one by one while all solution cells stay reachable for words, letters are assigned
+    // once all cells are chosen so they follow reading order
+    fn place_in_order(&mut self, candidates: Vec<usize>) -> bool {
+        let length = self.processed.chars().count();
+
+        for position in candidates {
+            if self.locations.len() == length {
+                break;
+            }
+            if self.fixed.contains(&position) || !self.is_valid_placement(position / self.cols, position % self.cols) {
+                continue;
+            }
+
+            self.locations.insert(position, '?');
+            if !self.revalidate_solution() {
+                self.locations.remove(&position);
+            }
+        }
+
+        if self.locations.len() < length {
+            self.locations.clear();
+            return false;
+        }
+
+        let mut positions: Vec<usize> = self.locations.keys().copied().collect();
+        positions.sort();
+        self.locations = positions.into_iter().zip(self.processed.chars()).collect();
         return true;
     }
 
     // We will go through shuffled cluster and try to put the solution on board
-    fn place_in_cluster(&mut self, cluster: Vec<(usize, usize)>) -> bool{
-        let shuffle_cluster: Vec<_> = cluster.choose_multiple(&mut thread_rng(), cluster.len()).cloned().collect();
-        let cluster_item = self.processed.chars().nth(self.locations.len()).unwrap();
+    fn place_in_cluster<R: Rng>(&mut self, cluster: Vec<(usize, usize)>, rng: &mut R) -> bool{
+        let shuffle_cluster: Vec<_> = cluster.choose_multiple(rng, cluster.len()).cloned().collect();
+        let Some(cluster_item) = self.processed.chars().nth(self.locations.len()) else {
+            return false;
+        };
 
         for (row, col) in shuffle_cluster {
+            if self.fixed.contains(&((row * self.cols) + col)) {
+                continue;
+            }
+
             if self.is_valid_placement(row, col) {
                 let current_location: usize = (row * self.cols) + col;
                 self.locations.insert(current_location, cluster_item);
-                if (!self.revalidate_solution()) {
+                if !self.revalidate_solution() {
                     self.locations.remove(&current_location);
                 } else {
                     return true;
@@ -96,8 +202,8 @@ impl Solution {
     }
 
     fn is_valid_placement(&self, row: usize, col: usize) -> bool {
-        for DIRECTION_MATRIX_CELL in Direction::DIRECTION_MATRIX() {
-            if self.is_valid_in_line(1, DIRECTION_MATRIX_CELL, row, col) {
+        for direction in &self.directions {
+            if self.is_valid_in_line(1, *direction, row, col) {
                 return true
             }
         }
@@ -110,23 +216,29 @@ impl Solution {
         let col_direction = (direction_index.getCol() * depth) + i32::try_from(col).unwrap();
         let directional_idx = (row_direction * i32::try_from(self.cols).unwrap()) + col_direction;
 
-        if row_direction != i32::try_from(row).unwrap() || col_direction != i32::try_from(col).unwrap() {
-            if row_direction >= 0 && col_direction >= 0 && row_direction < (i32::try_from(self.rows).unwrap()) && col_direction < (i32::try_from(self.cols).unwrap()) {
-                if self.locations.get(&(usize::try_from(directional_idx).unwrap())).is_none() {
-                    if depth == i32::try_from(MAX).unwrap() {
-                        return true;
-                    }
-                    return self.is_valid_in_line(depth + 1, direction_index, row, col);
-                }
-            }
+        // Center direction never leaves the position
+        if row_direction == i32::try_from(row).unwrap() && col_direction == i32::try_from(col).unwrap() {
+            return false;
         }
 
-        return false;
+        if row_direction < 0 || col_direction < 0 || row_direction >= (i32::try_from(self.rows).unwrap()) || col_direction >= (i32::try_from(self.cols).unwrap()) {
+            return false;
+        }
+
+        let directional_idx = usize::try_from(directional_idx).unwrap();
+        if self.locations.contains_key(&directional_idx) || self.blocked.contains(&directional_idx) {
+            return false;
+        }
+
+        if depth == i32::try_from(MAX).unwrap() {
+            return true;
+        }
+        return self.is_valid_in_line(depth + 1, direction_index, row, col);
     }
 
     fn revalidate_solution(&self) -> bool {
         let mut is_valid_board = true;
-        for (position, c) in &self.locations {
+        for position in self.locations.keys() {
             let row = *position / self.cols;
             let col = *position % self.cols;
 
@@ -140,7 +252,18 @@ impl Solution {
         return is_valid_board
     }
 
-    pub fn print_solution_on_board(&mut self) {
+    pub fn print_solution_on_board(&self) {
+        print!("{}", self);
+    }
+}
+
+// Blank for every letter of phrase, spaces and punctuation are kept so word lengths can be seen
+pub fn phrase_hint(phrase: &str) -> String {
+    phrase.chars().map(|c| if c.is_alphabetic() { '_' } else { c }).collect()
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut board: Vec<char> = vec!['?'; self.rows * self.cols];
         for (position, c) in &self.locations {
             board[*position] =  *c;
@@ -149,97 +272,171 @@ impl Solution {
         for row in 0..self.rows {
             for col in 0..self.cols {
                 let current_position = row * self.cols + col;
-                print!("{}\t", board.iter().nth(current_position).unwrap());
+                write!(f, "{}\t", board[current_position])?;
             }
 
-            println!("");
+            writeln!(f)?;
         }
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::ascii::AsciiExt;
     use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
 
     #[test]
     fn test_word() {
-        let solution = Solution::new("bike", 5, 5);
+        let solution = Solution::new("bike", 5, 5).unwrap();
 
         assert_eq!(solution.processed, "bike")
     }
 
     #[test]
     fn test_case_insensitive() {
-        let solution = Solution::new("BIKE", 5, 5);
+        let solution = Solution::new("BIKE", 5, 5).unwrap();
 
         assert_eq!(solution.processed, "bike")
     }
 
     #[test]
     fn test_sentence() {
-        let solution = Solution::new("I was driving my bike down the road", 100, 100);
+        let solution = Solution::new("I was driving my bike down the road", 100, 100).unwrap();
 
         assert_eq!(solution.processed, "iwasdrivingmybikedowntheroad")
     }
 
     #[test]
     fn test_numbers() {
-        let solution = Solution::new("I was driving my 2 bikes down the 14 road", 100, 100);
+        let solution = Solution::new("I was driving my 2 bikes down the 14 road", 100, 100).unwrap();
 
         assert_eq!(solution.processed, "iwasdrivingmybikesdowntheroad")
     }
 
     #[test]
     fn test_special() {
-        let solution = Solution::new("I was driving my bike, which is blue, down the road!", 100, 100);
+        let solution = Solution::new("I was driving my bike, which is blue, down the road!", 100, 100).unwrap();
 
         assert_eq!(solution.processed, "iwasdrivingmybikewhichisbluedowntheroad")
     }
 
+    #[test]
+    fn test_phrase() {
+        let solution = Solution::new("  I was   driving,\tmy Škoda!", 100, 100).unwrap();
+
+        assert_eq!(solution.phrase(), "I was driving, my Škoda!");
+        assert_eq!(solution.hint(), "_ ___ _______, __ _____!");
+        assert_eq!(solution.text(), "iwasdrivingmyškoda");
+    }
+
     #[test]
     fn test_utf() {
-        let solution = Solution::new("I was driving my Škoda bike, which is blue, down the road!", 100, 100);
+        let solution = Solution::new("I was driving my Škoda bike, which is blue, down the road!", 100, 100).unwrap();
 
         assert_eq!(solution.processed, "iwasdrivingmyškodabikewhichisbluedowntheroad")
     }
 
     #[test]
-    #[should_panic("empty")]
     fn test_empty() {
-        let mut solution = Solution::new("      ", 5, 5);
+        let solution = Solution::new("      ", 5, 5);
+
+        assert_eq!(solution.err(), Some(CrosswordoError::EmptySolution));
     }
 
     #[test]
-    #[should_panic("empty")]
     fn test_blank() {
-        let mut solution = Solution::new("", 5, 5);
+        let solution = Solution::new("", 5, 5);
+
+        assert_eq!(solution.err(), Some(CrosswordoError::EmptySolution));
     }
 
     #[test]
-    #[should_panic("too long")]
     fn test_too_long() {
-        let mut solution = Solution::new("This is looong very long text", 5, 5);
+        let solution = Solution::new("This is looong very long text", 5, 5);
+
+        assert_eq!(solution.err(), Some(CrosswordoError::SolutionTooLong { length: 24, max_length: 12 }));
+    }
+
+    #[test]
+    fn test_utf_length() {
+        // 8 letters in 9 bytes fit half of 4x4 board
+        assert!(Solution::new("stolička", 4, 4).is_ok());
+
+        let mut solution = Solution::new("stolička", 6, 6).unwrap();
+        assert!(solution.calculate_constraints(&mut ChaCha8Rng::seed_from_u64(1)));
+
+        let mut positions: Vec<&usize> = solution.locations.keys().collect();
+        positions.sort();
+        let letters: String = positions.into_iter().map(|position| solution.locations[position]).collect();
+        assert_eq!(letters, "stolička");
     }
 
     #[test]
     fn test_is_valid() {
-        let mut solution = Solution::new("testtesttest", 4, 6);
-        let result = solution.calculate_constraints();
+        let mut solution = Solution::new("testtesttest", 4, 6).unwrap();
+        let result = solution.calculate_constraints(&mut ChaCha8Rng::seed_from_u64(42));
 
         if result {
-            let mut prevItem: usize = 0;
+            let mut prev_item: usize = 0;
             for item in solution.locations.keys() {
-                let range = (prevItem..*item);
-                if range.count() > 5 {
-                    assert!(false)
-                }
+                let range = prev_item..*item;
+                assert!(range.count() <= 5);
 
-                prevItem = *item;
+                prev_item = *item;
             }
         }
+    }
+
+    #[test]
+    fn test_template_cells_are_avoided() {
+        let template = Template::parse("ab???\n#????\n??c??\n?????\n?????").unwrap();
+        let mut solution = Solution::new("xyzw", 5, 5).unwrap().with_template(&template);
+
+        assert!(solution.calculate_constraints(&mut ChaCha8Rng::seed_from_u64(1)));
+        for position in [0, 1, 5, 12] {
+            assert!(!solution.locations.contains_key(&position));
+        }
+    }
+
+    #[test]
+    fn test_strategies() {
+        let strategies = [
+            PlacementStrategy::Clusters, PlacementStrategy::Uniform, PlacementStrategy::End,
+            PlacementStrategy::Border, PlacementStrategy::Spiral, PlacementStrategy::Bottom,
+        ];
+
+        for strategy in strategies {
+            let mut solution = Solution::new("abcd", 5, 5).unwrap().with_strategy(strategy);
+            assert!(solution.calculate_constraints(&mut ChaCha8Rng::seed_from_u64(1)), "{:?}", strategy);
+
+            let mut positions: Vec<&usize> = solution.locations.keys().collect();
+            positions.sort();
+            let letters: String = positions.iter().map(|position| solution.locations[position]).collect();
+            assert_eq!(letters, "abcd", "{:?}", strategy);
+        }
+    }
+
+    #[test]
+    fn test_bottom_strategy() {
+        let mut solution = Solution::new("abcd", 5, 5).unwrap().with_strategy(PlacementStrategy::Bottom);
+        assert!(solution.calculate_constraints(&mut ChaCha8Rng::seed_from_u64(1)));
+
+        assert_eq!(solution.locations, HashMap::from([(21, 'a'), (22, 'b'), (23, 'c'), (24, 'd')]));
+    }
+
+    #[test]
+    fn test_same_seed_same_locations() {
+        let mut first = Solution::new("testtesttest", 4, 6).unwrap();
+        let mut second = Solution::new("testtesttest", 4, 6).unwrap();
+
+        let first_result = first.calculate_constraints(&mut ChaCha8Rng::seed_from_u64(7));
+        let second_result = second.calculate_constraints(&mut ChaCha8Rng::seed_from_u64(7));
 
-        assert!(true)
+        assert_eq!(first_result, second_result);
+        assert_eq!(first.locations, second.locations);
     }
 
 }
\ No newline at end of file
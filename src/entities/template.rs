@@ -0,0 +1,121 @@
+use crate::error::CrosswordoError;
+
+// Character of a cell which can never hold a letter, it stops every sequence like a solution cell
+pub const BLOCKED: char = '#';
+// Character of a cell which is filled by the generator
+pub const WILDCARD: char = '?';
+
+// Partially filled board the generation starts from, letters are fixed and will be kept on board
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    pub cells: Vec<Vec<char>>,
+}
+
+impl Template {
+    pub fn new(cells: Vec<Vec<char>>) -> Result<Self, CrosswordoError> {
+        // Each cell keeps a single letter, e.g. 'İ' lowercases to two characters and would not fit
+        let cells: Vec<Vec<char>> = cells
+            .into_iter()
+            .map(|row| row.into_iter().map(Template::lowercase).collect::<Result<Vec<char>, CrosswordoError>>())
+            .collect::<Result<Vec<Vec<char>>, CrosswordoError>>()?;
+
+        let cols = cells.first().map(|row| row.len()).unwrap_or(0);
+        if cells.iter().any(|row| row.len() != cols) {
+            return Err(CrosswordoError::InvalidTemplate(String::from("all rows need to have the same length")));
+        }
+
+        for row in &cells {
+            if let Some(c) = row.iter().find(|c| !(c.is_alphabetic() || **c == WILDCARD || **c == BLOCKED)) {
+                return Err(CrosswordoError::InvalidTemplate(format!("unsupported character '{}'", c)));
+            }
+        }
+
+        Ok(Template { cells })
+    }
+
+    fn lowercase(c: char) -> Result<char, CrosswordoError> {
+        let mut lowercase = c.to_lowercase();
+        match (lowercase.next(), lowercase.next()) {
+            (Some(lower), None) => Ok(lower),
+            _ => Err(CrosswordoError::InvalidTemplate(format!("letter '{}' has no single lowercase letter", c))),
+        }
+    }
+
+    // Each line is a row, whitespaces between cells are ignored so both "??b?i" and "? ? b ? i" are valid
+    pub fn parse(input: &str) -> Result<Self, CrosswordoError> {
+        let cells: Vec<Vec<char>> = input
+            .lines()
+            .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>())
+            .filter(|row| !row.is_empty())
+            .collect();
+
+        Template::new(cells)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cells.first().map(|row| row.len()).unwrap_or(0)
+    }
+
+    pub fn is_fixed(&self, row: usize, col: usize) -> bool {
+        self.cells[row][col] != WILDCARD
+    }
+
+    pub fn is_blocked(&self, row: usize, col: usize) -> bool {
+        self.cells[row][col] == BLOCKED
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static BOARD: [[char; 5]; 5] = [
+        ['?', '?', 'b', '?', 'i'],
+        ['?', '?', 'k', '?', '?'],
+        ['?', '?', '?', 'e', '?'],
+        ['?', '?', '?', '?', '?'],
+        ['?', '?', '?', '?', '?']
+    ];
+
+    #[test]
+    fn test_from_cells() {
+        let template = Template::new(BOARD.iter().map(|row| row.to_vec()).collect()).unwrap();
+
+        assert_eq!((template.rows(), template.cols()), (5, 5));
+        assert!(template.is_fixed(0, 2));
+        assert!(!template.is_fixed(0, 0));
+    }
+
+    #[test]
+    fn test_parse() {
+        let template = Template::parse("??B?i\n? ? k # ?\n\n").unwrap();
+
+        assert_eq!(template.cells, vec![vec!['?', '?', 'b', '?', 'i'], vec!['?', '?', 'k', '#', '?']]);
+        assert!(template.is_blocked(1, 3));
+    }
+
+    #[test]
+    fn test_uneven_rows() {
+        let template = Template::parse("???\n??");
+
+        assert!(matches!(template, Err(CrosswordoError::InvalidTemplate(_))));
+    }
+
+    #[test]
+    fn test_multi_char_lowercase() {
+        let template = Template::parse("?İ?\n???");
+
+        assert!(matches!(template, Err(CrosswordoError::InvalidTemplate(_))));
+    }
+
+    #[test]
+    fn test_unsupported_character() {
+        let template = Template::parse("??1\n???");
+
+        assert!(matches!(template, Err(CrosswordoError::InvalidTemplate(_))));
+    }
+}
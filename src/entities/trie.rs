@@ -1,10 +1,15 @@
 use std::collections::{HashMap, HashSet};
+use crate::entities::dictionary::Dictionary;
+use crate::entities::pattern::Pattern;
+use crate::error::CrosswordoError;
 use crate::MAX;
 
 // Define a structure for Trie nodes. Each node should store its children and a flag indicating if it represents the end of a word.
 pub struct TrieNode {
     nodes: HashMap<char, TrieNode>,
     word: Option<String>,
+    // Frequency of the word ending at this node
+    frequency: Option<f32>,
     eow: bool
 }
 
@@ -19,11 +24,18 @@ impl TrieNode {
         TrieNode {
             nodes: HashMap::new(),
             eow: false,
-            word: None
+            word: None,
+            frequency: None
         }
     }
 }
 
+impl Default for Trie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Trie {
     pub fn new() -> Self {
         Trie {
@@ -35,9 +47,25 @@ impl Trie {
 
 
     // The insert method will add a word to the Trie by iterating through its characters and creating child nodes as necessary.
-    pub fn insert(&mut self, word: &str) {
-        if word.len() < MAX {
-            panic!("Word is too short to be inserted in trie")
+    pub fn insert(&mut self, word: &str) -> Result<(), CrosswordoError> {
+        self.insert_node(word).map(|_| ())
+    }
+
+    // Inserts the word with its frequency, the higher frequency is kept when the word is inserted again
+    pub fn insert_with_frequency(&mut self, word: &str, frequency: f32) -> Result<(), CrosswordoError> {
+        if !frequency.is_finite() || frequency < 0.0 {
+            return Err(CrosswordoError::InvalidFrequency(word.to_string()));
+        }
+
+        let node = self.insert_node(word)?;
+        node.frequency = Some(node.frequency.map_or(frequency, |current| current.max(frequency)));
+
+        Ok(())
+    }
+
+    fn insert_node(&mut self, word: &str) -> Result<&mut TrieNode, CrosswordoError> {
+        if word.chars().count() < MAX {
+            return Err(CrosswordoError::WordTooShort(word.to_string()));
         }
 
         let mut root_node = &mut self.root;
@@ -45,49 +73,170 @@ impl Trie {
             root_node = root_node.nodes.entry(key).or_insert_with(TrieNode::new)
         }
 
-        root_node.word = Some(word.to_string());
-        root_node.eow = true;
-        self.items += 1;
+        // Words inserted again are counted once, the same way as in PackedTrie
+        if !root_node.eow {
+            root_node.word = Some(word.to_string());
+            root_node.eow = true;
+            self.items += 1;
+        }
+
+        Ok(root_node)
     }
 
     // The search method will check if a word exists in the Trie by traversing the nodes according to the word's characters or wildcards (unknown characters).
     pub fn search(&self, sequence: &str) -> HashSet<String> {
-        let root_node = &self.root;
-        let mut items: HashSet<String> = HashSet::new();
-        // This function ensures traversing the Trie and passing available words in an array
-        self.dfs_pattern_search(root_node, sequence, 0, &mut items);
+        self.search_iter(sequence).map(String::from).collect()
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.find(word).is_some_and(|node| node.eow)
+    }
+
+    pub fn frequency(&self, word: &str) -> Option<f32> {
+        self.find(word).and_then(|node| node.frequency)
+    }
+
+    fn find(&self, word: &str) -> Option<&TrieNode> {
+        let mut root_node = &self.root;
+        for key in word.chars() {
+            root_node = root_node.nodes.get(&key)?;
+        }
+
+        Some(root_node)
+    }
 
-        items
+    // Lazy form of search, matching words are borrowed from the trie
+    pub fn search_iter(&self, sequence: &str) -> TrieSearch<'_> {
+        self.matches(&Pattern::sequence(sequence))
     }
 
-    // Recursive DFS function to find words that match the current partial pattern.
-    fn dfs_pattern_search(&self, mut root_node: &TrieNode, sequence: &str, idx: usize, mut items: &mut HashSet<String>) -> bool {
-        if sequence.len() == idx {
-            if root_node.eow {
-                let word = root_node.word.clone().unwrap();
-                // Collect all words that match the pattern
-                items.insert(word);
+    // Words matching a pattern with character classes, runs and prefixes
+    pub fn matches(&self, pattern: &Pattern) -> TrieSearch<'_> {
+        TrieSearch {
+            stack: vec![(&self.root, 0, pattern.start())],
+            pattern: pattern.clone(),
+        }
+    }
+
+    pub fn count_matches(&self, pattern: &Pattern) -> usize {
+        Self::count_matches_from(&self.root, pattern, 0, pattern.start())
+    }
+
+    fn count_matches_from(root_node: &TrieNode, pattern: &Pattern, depth: usize, states: u128) -> usize {
+        let mut count = usize::from(root_node.eow && pattern.accepts(states, depth));
+        if !pattern.continues(states, depth) {
+            return count;
+        }
+
+        if let Some(c) = pattern.literal(states, depth) {
+            if let Some(next_node) = root_node.nodes.get(&c) {
+                count += Self::count_matches_from(next_node, pattern, depth + 1, pattern.step(states, depth, c));
+            }
+            return count;
+        }
+
+        for (c, next_node) in &root_node.nodes {
+            let next_states = pattern.step(states, depth, *c);
+            if next_states != 0 {
+                count += Self::count_matches_from(next_node, pattern, depth + 1, next_states);
             }
-            return root_node.eow;
         }
 
-        // Continue until eow or could not find the pattern occurs
-        if sequence.chars().nth(idx).eq(&Some('?')) {
-            // Handle the wildcards and traverse every child node of current root accordingly.
-            for nextNode in root_node.nodes.values() {
-                let result = self.dfs_pattern_search(&nextNode, sequence, idx + 1, items);
+        count
+    }
+
+    // Number of words matching the pattern, nothing is allocated
+    pub fn count(&self, sequence: &str) -> usize {
+        Self::count_from(&self.root, sequence)
+    }
+
+    fn count_from(root_node: &TrieNode, sequence: &str) -> usize {
+        let Some(c) = sequence.chars().next() else {
+            return usize::from(root_node.eow);
+        };
+
+        let rest = &sequence[c.len_utf8()..];
+        if c == '?' {
+            return root_node.nodes.values().map(|next_node| Self::count_from(next_node, rest)).sum();
+        }
+
+        return root_node.nodes.get(&c).map_or(0, |next_node| Self::count_from(next_node, rest));
+    }
+
+}
+
+// Depth first traversal of nodes matching the pattern, one word is found per call of next.
+// Every node keeps the states of the pattern reached by the characters leading to it
+pub struct TrieSearch<'a> {
+    pattern: Pattern,
+    stack: Vec<(&'a TrieNode, usize, u128)>,
+}
+
+impl<'a> Iterator for TrieSearch<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        while let Some((node, depth, states)) = self.stack.pop() {
+            if self.pattern.continues(states, depth) {
+                if let Some(c) = self.pattern.literal(states, depth) {
+                    if let Some(next_node) = node.nodes.get(&c) {
+                        self.stack.push((next_node, depth + 1, self.pattern.step(states, depth, c)));
+                    }
+                } else {
+                    for (c, next_node) in &node.nodes {
+                        let next_states = self.pattern.step(states, depth, *c);
+                        if next_states != 0 {
+                            self.stack.push((next_node, depth + 1, next_states));
+                        }
+                    }
+                }
+            }
+
+            if node.eow && self.pattern.accepts(states, depth) {
+                return node.word.as_deref();
             }
-        } else if let Some(nextNode) = root_node.nodes.get(&sequence.chars().nth(idx).unwrap()) {
-            // If the node is not a wildcards follow the child node if exists
-            return self.dfs_pattern_search(nextNode, sequence, idx + 1, items)
         }
-        return false;
+
+        None
+    }
+}
+
+impl Dictionary for Trie {
+    fn search(&self, sequence: &str) -> HashSet<String> {
+        Trie::search(self, sequence)
+    }
+
+    fn search_iter<'a>(&'a self, sequence: &str) -> Box<dyn Iterator<Item = String> + 'a> {
+        Box::new(Trie::search_iter(self, sequence).map(String::from))
+    }
+
+    fn count(&self, sequence: &str) -> usize {
+        Trie::count(self, sequence)
+    }
+
+    fn matches<'a>(&'a self, pattern: &Pattern) -> Box<dyn Iterator<Item = String> + 'a> {
+        Box::new(Trie::matches(self, pattern).map(String::from))
+    }
+
+    fn count_matches(&self, pattern: &Pattern) -> usize {
+        Trie::count_matches(self, pattern)
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        Trie::contains(self, word)
+    }
+
+    fn frequency(&self, word: &str) -> Option<f32> {
+        Trie::frequency(self, word)
+    }
+
+    fn len(&self) -> usize {
+        self.items
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::ascii::AsciiExt;
     use super::*;
 
     #[test]
@@ -95,16 +244,80 @@ mod tests {
         let word = "motorcycle";
         let mut trie = Trie::new();
 
-        trie.insert(word);
+        assert!(trie.insert(word).is_ok());
     }
 
     #[test]
-    #[should_panic(expected = "too short")]
     fn test_small_word() {
-        let shortWord = "car";
+        let short_word = "ca";
         let mut trie = Trie::new();
 
-        trie.insert(shortWord);
+        assert_eq!(trie.insert(short_word), Err(CrosswordoError::WordTooShort(short_word.to_string())));
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut trie = Trie::new();
+        trie.insert("star").unwrap();
+        trie.insert("start").unwrap();
+
+        assert!(trie.contains("star"));
+        assert!(!trie.contains("sta"));
+        assert!(!trie.contains("s??r"));
+    }
+
+    #[test]
+    fn test_frequency() {
+        let mut trie = Trie::new();
+        trie.insert_with_frequency("star", 12.5).unwrap();
+        trie.insert_with_frequency("star", 3.0).unwrap();
+        trie.insert("start").unwrap();
+
+        assert_eq!(trie.frequency("star"), Some(12.5));
+        assert_eq!(trie.frequency("start"), None);
+        assert_eq!(trie.frequency("sta"), None);
+        assert_eq!(trie.insert_with_frequency("stop", -1.0), Err(CrosswordoError::InvalidFrequency(String::from("stop"))));
+        assert_eq!(trie.insert_with_frequency("stop", f32::NAN), Err(CrosswordoError::InvalidFrequency(String::from("stop"))));
+    }
+
+    #[test]
+    fn test_count_and_iter() {
+        let dictionary = vec!["space", "place", "craze", "crate", "state", "plate", "blade", "blato", "šťava"];
+
+        let mut trie = Trie::new();
+        for word in dictionary {
+            trie.insert(word).unwrap();
+        }
+
+        assert_eq!(trie.count("??a?e"), 7);
+        assert_eq!(trie.count("?ť???"), 1);
+        assert_eq!(trie.count("water"), 0);
+        assert_eq!(trie.search_iter("??a?e").map(String::from).collect::<HashSet<String>>(), trie.search("??a?e"));
+
+        let mut matches = trie.search_iter("bla??");
+        assert!(matches.next().is_some());
+        assert!(matches.next().is_some());
+        assert_eq!(matches.next(), None);
+    }
+
+    #[test]
+    fn test_pattern_matches() {
+        let dictionary = vec!["star", "stare", "story", "stories", "start", "staring", "spot"];
+
+        let mut trie = Trie::new();
+        for word in dictionary {
+            trie.insert(word).unwrap();
+        }
+
+        let matches = |pattern: &Pattern| trie.matches(pattern).map(String::from).collect::<HashSet<String>>();
+
+        assert_eq!(matches(&Pattern::parse("st[aeiou]r?").unwrap()), HashSet::from(["stare".to_string(), "start".to_string(), "story".to_string()]));
+        assert_eq!(matches(&Pattern::parse("s[^t]*").unwrap()), HashSet::from(["spot".to_string()]));
+        assert_eq!(matches(&Pattern::parse("st*r*").unwrap()).len(), 6);
+        assert_eq!(matches(&Pattern::parse("st*r*").unwrap().with_max_length(5)).len(), 4);
+        assert_eq!(matches(&Pattern::sequence("stari?gs").with_prefixes(true)), HashSet::from(["star".to_string(), "staring".to_string()]));
+        assert_eq!(trie.count_matches(&Pattern::parse("st*r*").unwrap()), 6);
+        assert_eq!(trie.count_matches(&Pattern::sequence("sto???").with_prefixes(true).with_min_length(4)), 1);
     }
 
     #[test]
@@ -114,7 +327,7 @@ mod tests {
         let mut trie = Trie::new();
 
         for word in dictionary {
-            trie.insert(word);
+            trie.insert(word).unwrap();
         }
 
         let result = trie.search("space");
@@ -129,12 +342,12 @@ mod tests {
         let mut trie = Trie::new();
 
         for word in dictionary {
-            trie.insert(word);
+            trie.insert(word).unwrap();
         }
 
         let result = trie.search("space");
 
-        assert_eq!(result.iter().nth(0).unwrap(), "space");
+        assert_eq!(result.iter().next().unwrap(), "space");
     }
 
     #[test]
@@ -144,15 +357,12 @@ mod tests {
         let mut trie = Trie::new();
 
         for word in dictionary {
-            trie.insert(word);
+            trie.insert(word).unwrap();
         }
 
         let result = trie.search("water");
 
-        match result.iter().nth(0) {
-            Some(value) => assert!(false),
-            None => assert!(true)
-        }
+        assert!(result.iter().next().is_none());
     }
 
     #[test]
@@ -162,7 +372,7 @@ mod tests {
         let mut trie = Trie::new();
 
         for word in dictionary {
-            trie.insert(word);
+            trie.insert(word).unwrap();
         }
 
         let result = trie.search("??a?e");
@@ -177,14 +387,14 @@ mod tests {
         let mut trie = Trie::new();
 
         for word in dictionary {
-            trie.insert(word);
+            trie.insert(word).unwrap();
         }
 
-        let mut result = trie.search("??a?e");
+        let result = trie.search("??a?e");
 
 
-        let toCompare = HashSet::from(["state".to_string(), "space".to_string(), "blade".to_string(), "craze".to_string(), "crate".to_string(), "plate".to_string(), "place".to_string()]);
-        assert_eq!(result, toCompare)
+        let to_compare = HashSet::from(["state".to_string(), "space".to_string(), "blade".to_string(), "craze".to_string(), "crate".to_string(), "plate".to_string(), "place".to_string()]);
+        assert_eq!(result, to_compare)
     }
 
     #[test]
@@ -194,13 +404,13 @@ mod tests {
         let mut trie = Trie::new();
 
         for word in dictionary {
-            trie.insert(word);
+            trie.insert(word).unwrap();
         }
 
-        let mut result = trie.search("s?a??");
+        let result = trie.search("s?a??");
 
-        let toCompare = HashSet::from(["stare".to_string(), "start".to_string()]);
-        assert_eq!(result, toCompare);
+        let to_compare = HashSet::from(["stare".to_string(), "start".to_string()]);
+        assert_eq!(result, to_compare);
     }
 
 
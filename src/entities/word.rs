@@ -1,6 +1,7 @@
+use serde::{Deserialize, Serialize};
 use crate::entities::direction::Direction;
 
-#[derive(Eq, PartialEq, Hash, Clone)]
+#[derive(Eq, PartialEq, Hash, Clone, Debug, Serialize, Deserialize)]
 pub struct Word {
     pub direction: Direction,
     length: usize,
@@ -14,4 +15,12 @@ impl Word {
             coords, direction, length: value.len(), word: value,
         }
     }
+
+    // Row and column of each letter from the first to the last one
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        let (row, col) = self.coords;
+        (0..self.word.chars().count() as i32)
+            .map(|depth| ((row as i32 + self.direction.getRow() * depth) as usize, (col as i32 + self.direction.getCol() * depth) as usize))
+            .collect()
+    }
 }
\ No newline at end of file
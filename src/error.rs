@@ -0,0 +1,65 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrosswordoError {
+    // Board needs at least MAX + 1 rows and cols so any word can be placed
+    BoardTooSmall { rows: usize, cols: usize },
+    EmptySolution,
+    SolutionTooLong { length: usize, max_length: usize },
+    WordTooShort(String),
+    InvalidTemplate(String),
+    // Configuration does not allow any direction to place words in
+    NoDirections,
+    // Solution characters could not be spread on board while keeping cells reachable for words
+    SolutionPlacementFailed,
+    // No word from dictionary fits any sequence on board
+    NoAvailableWords,
+    // Required words which could not be placed on board in the most successful attempt
+    RequiredWordsNotPlaced(Vec<String>),
+    BacktrackingFailed,
+    // Generation hit one of the configured limits, board keeps the words placed so far
+    BudgetExhausted(String),
+    Cancelled,
+    InvalidFiller(String),
+    // Externally supplied grid could not be read
+    InvalidGrid(String),
+    // Word frequency is negative or not a number
+    InvalidFrequency(String),
+    // Compiled dictionary could not be read or written
+    InvalidDictionary(String),
+    // Dictionary search pattern could not be parsed
+    InvalidPattern(String),
+    // Placed words which can be read on board more than once
+    DuplicateWords(Vec<String>),
+    // Puzzle could not be converted from or to an exported format
+    Serialization(String),
+}
+
+impl fmt::Display for CrosswordoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrosswordoError::BoardTooSmall { rows, cols } => write!(f, "The board {}x{} seems too small to handle", rows, cols),
+            CrosswordoError::EmptySolution => write!(f, "Solution is empty, support for no solution is not available"),
+            CrosswordoError::SolutionTooLong { length, max_length } => write!(f, "The solution with {} characters is too long, please ensure your solution does not exceed {}", length, max_length),
+            CrosswordoError::WordTooShort(word) => write!(f, "Word '{}' is too short to be inserted in trie", word),
+            CrosswordoError::InvalidTemplate(reason) => write!(f, "Template cannot be used, {}", reason),
+            CrosswordoError::NoDirections => write!(f, "At least one direction other than CENTER has to be allowed"),
+            CrosswordoError::SolutionPlacementFailed => write!(f, "Could not place solution, try again"),
+            CrosswordoError::NoAvailableWords => write!(f, "Cannot be initiated, no word fits the board"),
+            CrosswordoError::RequiredWordsNotPlaced(words) => write!(f, "Required words could not be placed: {}", words.join(", ")),
+            CrosswordoError::BacktrackingFailed => write!(f, "Backtracking failure, board could not be populated"),
+            CrosswordoError::BudgetExhausted(limit) => write!(f, "Generation budget exhausted after {}", limit),
+            CrosswordoError::Cancelled => write!(f, "Generation was cancelled"),
+            CrosswordoError::InvalidFiller(reason) => write!(f, "Filler cannot be used, {}", reason),
+            CrosswordoError::InvalidGrid(reason) => write!(f, "Grid cannot be used, {}", reason),
+            CrosswordoError::InvalidFrequency(word) => write!(f, "Frequency of {} has to be a non-negative number", word),
+            CrosswordoError::InvalidDictionary(reason) => write!(f, "Compiled dictionary cannot be used, {}", reason),
+            CrosswordoError::InvalidPattern(reason) => write!(f, "Pattern cannot be used, {}", reason),
+            CrosswordoError::DuplicateWords(words) => write!(f, "Words can be found on board more than once: {}", words.join(", ")),
+            CrosswordoError::Serialization(reason) => write!(f, "Puzzle could not be serialized, {}", reason),
+        }
+    }
+}
+
+impl Error for CrosswordoError {}
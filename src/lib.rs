@@ -0,0 +1,26 @@
+// Directions are exposed as associated constructors (Direction::EAST()) and getters follow getRow/getCol naming
+#![allow(non_snake_case)]
+#![allow(clippy::needless_return)]
+
+pub mod entities;
+pub mod error;
+pub mod logic;
+pub mod render;
+#[cfg(test)]
+mod test_utils;
+
+pub use crate::entities::puzzle::Puzzle;
+pub use crate::entities::dictionary::{Dictionary, DictionaryBackend};
+pub use crate::entities::direction::Direction;
+pub use crate::entities::pattern::Pattern;
+pub use crate::entities::placement::PlacementStrategy;
+pub use crate::error::CrosswordoError;
+pub use crate::logic::budget::{Budget, CancellationToken};
+pub use crate::logic::config::GeneratorConfig;
+pub use crate::logic::filler::Filler;
+pub use crate::logic::generator::PuzzleBuilder;
+pub use crate::logic::solver::{solve, SolverReport};
+pub use crate::logic::observer::{GenerationEvent, GenerationObserver};
+
+// Minimal length of a word which can be placed on board
+pub const MAX: usize = 3;
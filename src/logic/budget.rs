@@ -0,0 +1,94 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use crate::error::CrosswordoError;
+
+// Limits of a single generation, unset limits are not checked
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Budget {
+    // Wall clock time measured from the start of generation
+    pub time_limit: Option<Duration>,
+    // Number of words put on board including the ones removed later
+    pub max_placements: Option<usize>,
+    // Number of words removed from board when a branch of search fails
+    pub max_backtracks: Option<usize>,
+}
+
+impl Budget {
+    pub fn check(&self, started: Instant, placements: usize, backtracks: usize) -> Result<(), CrosswordoError> {
+        if let Some(time_limit) = self.time_limit {
+            if started.elapsed() >= time_limit {
+                return Err(CrosswordoError::BudgetExhausted(format!("time limit of {} ms", time_limit.as_millis())));
+            }
+        }
+        if let Some(max_placements) = self.max_placements {
+            if placements >= max_placements {
+                return Err(CrosswordoError::BudgetExhausted(format!("{} placements", max_placements)));
+            }
+        }
+        if let Some(max_backtracks) = self.max_backtracks {
+            if backtracks >= max_backtracks {
+                return Err(CrosswordoError::BudgetExhausted(format!("{} backtracks", max_backtracks)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Shared flag to stop a running generation from another thread, clones cancel the same generation
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited() {
+        let budget = Budget::default();
+
+        assert_eq!(budget.check(Instant::now(), usize::MAX - 1, usize::MAX - 1), Ok(()));
+    }
+
+    #[test]
+    fn test_limits() {
+        let budget = Budget { max_placements: Some(10), max_backtracks: Some(5), ..Budget::default() };
+
+        assert_eq!(budget.check(Instant::now(), 9, 4), Ok(()));
+        assert_eq!(budget.check(Instant::now(), 10, 4), Err(CrosswordoError::BudgetExhausted(String::from("10 placements"))));
+        assert_eq!(budget.check(Instant::now(), 9, 5), Err(CrosswordoError::BudgetExhausted(String::from("5 backtracks"))));
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let budget = Budget { time_limit: Some(Duration::ZERO), ..Budget::default() };
+
+        assert!(matches!(budget.check(Instant::now(), 0, 0), Err(CrosswordoError::BudgetExhausted(_))));
+    }
+
+    #[test]
+    fn test_cancellation_is_shared() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        clone.cancel();
+
+        assert!(token.is_cancelled());
+    }
+}
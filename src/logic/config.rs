@@ -0,0 +1,77 @@
+use crate::entities::dictionary::DictionaryBackend;
+use crate::entities::direction::Direction;
+use crate::entities::placement::PlacementStrategy;
+use crate::logic::budget::{Budget, CancellationToken};
+use crate::logic::filler::Filler;
+use crate::entities::template::Template;
+
+// Parameters of a single puzzle shared by the controller and the entities it creates
+#[derive(Clone, Debug)]
+pub struct GeneratorConfig {
+    pub rows: usize,
+    pub cols: usize,
+    pub solution: String,
+    // Where on board the solution letters are put
+    pub placement: PlacementStrategy,
+    // Structure the dictionary is loaded into, the packed one saves memory
+    pub dictionary_backend: DictionaryBackend,
+    // Random seed is picked by controller when not set
+    pub seed: Option<u64>,
+    // Directions in which words can be placed, CENTER is ignored
+    pub directions: Vec<Direction>,
+    // Board is started from the template, its size takes precedence over rows and cols
+    pub template: Option<Template>,
+    // Words placed first, generation fails when any of them cannot be placed
+    pub required_words: Vec<String>,
+    // Limits after which the generation is stopped
+    pub budget: Budget,
+    pub cancellation: Option<CancellationToken>,
+    // Failed generation returns the board with the most covered cells instead of an error
+    pub allow_partial: bool,
+    // Empty cells left after generation are filled with random letters
+    pub filler: Option<Filler>,
+    // Finished board with a placed word readable more than once fails instead of only being reported
+    pub reject_duplicates: bool,
+    // Words can share letters with other words
+    pub crossings: bool,
+    // Solution letters are never shared with words, otherwise the solution cannot be read from cells left over
+    pub strict_solution: bool,
+    // Preferred share of word letters already on board from 0.0 (no overlaps) to 1.0, candidates are not
+    // ordered by overlaps when not set
+    pub overlap_density: Option<f64>,
+    // Words can end before the edge of board, a blocked cell or a solution cell instead of filling the whole sequence
+    pub short_words: bool,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            rows: 5, cols: 5,
+            solution: String::new(),
+            placement: PlacementStrategy::default(),
+            dictionary_backend: DictionaryBackend::default(),
+            seed: None,
+            directions: Direction::ALL().to_vec(),
+            template: None,
+            required_words: Vec::new(),
+            budget: Budget::default(),
+            cancellation: None,
+            allow_partial: false,
+            filler: None,
+            reject_duplicates: false,
+            crossings: true,
+            strict_solution: true,
+            overlap_density: None,
+            short_words: false,
+        }
+    }
+}
+
+impl GeneratorConfig {
+    pub fn size(&self) -> (usize, usize) {
+        match &self.template {
+            Some(template) => (template.rows(), template.cols()),
+            None => (self.rows, self.cols),
+        }
+    }
+}
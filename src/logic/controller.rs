@@ -1,15 +1,28 @@
 use std::cmp::max;
-use std::collections::{BTreeMap, HashMap, HashSet};
-use std::hash::Hash;
-use std::time::SystemTime;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+use log::{debug, info, trace, warn};
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
 use crate::entities::board::Board;
 use crate::entities::direction::Direction;
+use crate::entities::puzzle::Puzzle;
 use crate::entities::solution::Solution;
+use crate::entities::dictionary::{Dictionary, DictionaryBackend};
+use crate::entities::packed_trie::PackedTrie;
 use crate::entities::trie::Trie;
 use crate::entities::word::Word;
+use crate::error::CrosswordoError;
+use crate::logic::budget::{Budget, CancellationToken};
+use crate::logic::config::GeneratorConfig;
+use crate::logic::filler::Filler;
+use crate::logic::observer::{GenerationEvent, GenerationObserver};
+use crate::logic::verifier::{self, Verification};
 use crate::logic::wfc::WFC;
 use crate::MAX;
 
+#[allow(clippy::upper_case_acronyms)]
 pub enum LoopState {
     INITIALIZED, PLAYING, FINISHED, FAILED
 }
@@ -19,127 +32,417 @@ pub struct Controller {
     board: Board,
     solution: Solution,
     // Trie is necessary only for entropy search
-    dictionary: Trie,
+    dictionary: Box<dyn Dictionary>,
     // History holds information about word being placed and words that were already used
     history: Vec<Word>,
-    // Holds current state for a board. If a word is placed on board and game is not finished, it is
-    // recalculated in all possible directions
-    states: HashMap<usize, Vec<Word>>,
-    // Will hold information about sequences so no dictionary search is needed
-    sequence_cache: HashMap<String, HashSet<String>>,
+    // Holds current state for a board, the number of words which can start at each cell. If a word is placed on
+    // board and game is not finished, it is recalculated in all possible directions
+    states: BTreeMap<usize, usize>,
+    // Will hold the number of words matching each sequence so no dictionary search is needed
+    sequence_cache: HashMap<String, usize>,
+    // Every random decision is taken from this generator so the same seed reproduces the same puzzle
+    seed: u64,
+    rng: ChaCha8Rng,
+    directions: Vec<Direction>,
+    // Theme words which have to be placed before the board is filled from dictionary
+    required_words: Vec<String>,
+    observer: Option<Box<dyn GenerationObserver>>,
+    budget: Budget,
+    cancellation: Option<CancellationToken>,
+    // Progress of the current generation checked against the budget
+    started: Instant,
+    placements: usize,
+    backtracks: usize,
+    allow_partial: bool,
+    filler: Option<Filler>,
+    // Words of the board with the fewest empty cells seen during generation
+    best: Vec<Word>,
+    best_remaining: usize,
+    reject_duplicates: bool,
+    overlap_density: Option<f64>,
+    short_words: bool,
 }
 
 
 impl Controller {
-    pub fn new(solution: &str, rows: usize, cols: usize, dictionary: Vec<String>) -> Self {
-        let mut trie = Trie::new();
+    // Words found in frequencies keep their frequency in the dictionary and are preferred when it is higher
+    pub fn new(config: &GeneratorConfig, dictionary: Vec<String>, frequencies: &HashMap<String, f32>) -> Result<Self, CrosswordoError> {
+        let mut words: Vec<String> = dictionary.into_iter().filter(|word| word.chars().count() >= MAX).collect();
+        words.extend(Self::required_words(config));
+
+        let dictionary: Box<dyn Dictionary> = match config.dictionary_backend {
+            DictionaryBackend::Trie => {
+                let mut trie = Trie::new();
+                for word in &words {
+                    match frequencies.get(word) {
+                        Some(frequency) => trie.insert_with_frequency(word, *frequency)?,
+                        None => trie.insert(word)?,
+                    }
+                }
+                Box::new(trie)
+            }
+            DictionaryBackend::Packed => Box::new(PackedTrie::weighted(&words, frequencies)?),
+        };
+        debug!("{} words loaded into dictionary", dictionary.len());
 
-        dictionary.iter()
-            .filter(|word| word.len() >= MAX)
-            .for_each(|word| trie.insert(word));
+        Self::with_dictionary(config, dictionary)
+    }
+
+    // Generates from a dictionary which is already loaded, e.g. a compiled one. Backend of config is not used and
+    // required words missing from the dictionary are placed but not checked by verification
+    pub fn with_dictionary(config: &GeneratorConfig, dictionary: Box<dyn Dictionary>) -> Result<Self, CrosswordoError> {
+        let (rows, cols) = config.size();
+        let seed = config.seed.unwrap_or_else(rand::random);
+
+        let directions: Vec<Direction> = config.directions
+            .iter()
+            .filter(|direction| **direction != Direction::CENTER())
+            .cloned()
+            .collect();
+        if directions.is_empty() {
+            return Err(CrosswordoError::NoDirections);
+        }
+
+        let mut board = Board::new(rows, cols)?
+            .with_directions(&directions)
+            .with_crossings(config.crossings)
+            .with_strict_solution(config.strict_solution);
+        let mut solution = Solution::new(&config.solution, rows, cols)?
+            .with_directions(&directions)
+            .with_strategy(config.placement);
+        if let Some(template) = &config.template {
+            board = board.with_template(template);
+            solution = solution.with_template(template);
+        }
 
         let mut controller = Controller {
             rows, cols,
-            board: Board::new(rows, cols),
-            solution: Solution::new(solution, rows, cols),
-            dictionary: trie,
+            board,
+            solution,
+            dictionary,
             history: Vec::new(),
-            states: HashMap::new(),
-            sequence_cache: HashMap::new()
+            states: BTreeMap::new(),
+            sequence_cache: HashMap::new(),
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            directions,
+            required_words: Self::required_words(config),
+            observer: None,
+            budget: config.budget,
+            cancellation: config.cancellation.clone(),
+            started: Instant::now(),
+            placements: 0,
+            backtracks: 0,
+            allow_partial: config.allow_partial,
+            filler: config.filler.clone(),
+            best: Vec::new(),
+            best_remaining: usize::MAX,
+            reject_duplicates: config.reject_duplicates,
+            overlap_density: config.overlap_density.map(|density| density.clamp(0.0, 1.0)),
+            short_words: config.short_words,
         };
 
         // FIXME : We could rather have special function for this
-        controller.prepare_solution();
+        controller.prepare_solution()?;
 
 
-        return controller;
+        return Ok(controller);
     }
 
+    fn required_words(config: &GeneratorConfig) -> Vec<String> {
+        let mut required_words: Vec<String> = Vec::new();
+        for word in config.required_words.iter().map(|word| word.to_lowercase()) {
+            if !required_words.contains(&word) {
+                required_words.push(word);
+            }
+        }
+
+        required_words
+    }
 
-    fn prepare_solution(&mut self) -> bool {
-        let state = self.solution.calculate_constraints();
 
-        if (!state) {
-            panic!("Could not place solution, try again")
+    fn prepare_solution(&mut self) -> Result<(), CrosswordoError> {
+        if !self.solution.calculate_constraints(&mut self.rng) {
+            return Err(CrosswordoError::SolutionPlacementFailed);
         }
 
-        self.solution.print_solution_on_board();
+        trace!("solution placed on board:\n{}", self.solution);
         self.board.put_solution_on_board(&self.solution);
 
-        state
+        Ok(())
     }
 
     pub fn perform_action(&mut self) {
-        if self.backtrack(0) {
-            println!("Backtracking success");
-        } else {
-            println!("Backtracking failure");
+        match self.generate() {
+            Ok(()) => info!("Backtracking success"),
+            Err(error) => warn!("{}", error)
+        }
+    }
+
+    // Places required words and runs the backtracking until the board is populated. When the budget is
+    // exhausted or generation is cancelled the board keeps the words placed so far, see Controller::puzzle
+    pub fn generate(&mut self) -> Result<(), CrosswordoError> {
+        self.started = Instant::now();
+        self.placements = 0;
+        self.backtracks = 0;
+        self.best = Vec::new();
+        self.best_remaining = usize::MAX;
+
+        // Best board is recorded by every placed word, so failing while required words are placed keeps it the
+        // same way as failing while the rest of board is filled
+        match self.populate() {
+            Ok(()) => debug!("board populated with {} words", self.history.len()),
+            Err(error @ (CrosswordoError::BacktrackingFailed | CrosswordoError::NoAvailableWords | CrosswordoError::BudgetExhausted(_)
+                | CrosswordoError::RequiredWordsNotPlaced(_))) if self.allow_partial => {
+                self.restore_best();
+                warn!("{}, keeping the best board with {} empty cells", error, self.board.count_empty_cells());
+            }
+            Err(error) => return Err(error),
+        }
+
+        if let Some(filler) = &self.filler {
+            let filled = filler.fill(&mut self.board, self.dictionary.as_ref(), &mut self.rng);
+            debug!("{} empty cells filled with filler letters", filled);
+        }
+
+        let verification = self.verify();
+        if !verification.unintended.is_empty() {
+            debug!("{} unintended dictionary words on board", verification.unintended.len());
+        }
+        if verification.is_ambiguous() {
+            if self.reject_duplicates {
+                return Err(CrosswordoError::DuplicateWords(verification.duplicate_words()));
+            }
+            warn!("words can be found on board more than once: {}", verification.duplicate_words().join(", "));
+        }
+
+        Ok(())
+    }
+
+    // Dictionary words readable on board compared with the placed ones
+    pub fn verify(&self) -> Verification {
+        verifier::verify(&self.board, self.dictionary.as_ref(), &self.history)
+    }
+
+    // Required words are placed first and the rest of board is filled for each of their arrangements until
+    // one of them can be completed
+    fn populate(&mut self) -> Result<(), CrosswordoError> {
+        // Longest words are the hardest to fit so they go first
+        let mut required_words = self.required_words.clone();
+        required_words.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+
+        let mut unplaced = required_words.clone();
+        if !self.place_required(&required_words, &mut unplaced)? {
+            // Every arrangement of required words was placed and none of them could be filled
+            if unplaced.is_empty() {
+                return Err(CrosswordoError::BacktrackingFailed);
+            }
+            return Err(CrosswordoError::RequiredWordsNotPlaced(unplaced));
+        }
+
+        Ok(())
+    }
+
+    fn fill(&mut self) -> Result<bool, CrosswordoError> {
+        debug!("{} required words placed, filling the rest of board", self.history.len());
+        // States left from another arrangement of required words belong to a different board
+        self.invalidate_all_states();
+
+        if self.board.is_board_populated() {
+            return Ok(true);
+        }
+        match self.backtrack(0) {
+            Err(CrosswordoError::NoAvailableWords) => Ok(false),
+            result => result,
         }
     }
 
+    // Replaces the words on board by the best ones, words shared by both boards stay in place
+    fn restore_best(&mut self) {
+        let best = std::mem::take(&mut self.best);
+        let common = self.history.iter().zip(&best).take_while(|(current, best)| current == best).count();
+
+        while self.history.len() > common {
+            let word = self.history[self.history.len() - 1].clone();
+            self.remove_word(&word);
+        }
+        for word in &best[common..] {
+            self.put_word(word);
+        }
+
+        self.best = best;
+    }
+
+    // Tries every placement of the first word and recursively places the rest, the board is filled once all of
+    // them are placed. Unplaced holds the words left over from the attempt which got furthest
+    fn place_required(&mut self, required_words: &[String], unplaced: &mut Vec<String>) -> Result<bool, CrosswordoError> {
+        let Some((word, rest)) = required_words.split_first() else {
+            unplaced.clear();
+            return self.fill();
+        };
+
+        let mut placements = self.board.find_placements(word);
+        placements.shuffle(&mut self.rng);
+
+        for placement in placements {
+            self.check_budget()?;
+            self.put_word(&placement);
+
+            if self.place_required(rest, unplaced)? {
+                return Ok(true);
+            }
+
+            self.remove_word(&placement);
+        }
+
+        if required_words.len() < unplaced.len() {
+            *unplaced = required_words.to_vec();
+        }
+        Ok(false)
+    }
+
+    pub fn set_observer(&mut self, observer: Box<dyn GenerationObserver>) {
+        self.observer = Some(observer);
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn solution(&self) -> &Solution {
+        &self.solution
+    }
+
+    pub fn placed_words(&self) -> &[Word] {
+        &self.history
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn puzzle(&self) -> Puzzle {
+        Puzzle::new(&self.board, &self.solution, &self.history, self.seed)
+    }
+
+    fn check_budget(&self) -> Result<(), CrosswordoError> {
+        if self.cancellation.as_ref().is_some_and(|token| token.is_cancelled()) {
+            return Err(CrosswordoError::Cancelled);
+        }
+
+        self.budget.check(self.started, self.placements, self.backtracks)
+    }
+
     // FIXME It fails probably due to errors with processing
-    fn backtrack(&mut self, step: usize) -> bool {
+    fn backtrack(&mut self, step: usize) -> Result<bool, CrosswordoError> {
+        self.check_budget()?;
+
         // Initiate all states
         // FIXME : It's explicit
-        self.calculate_possible_states();
+        self.calculate_possible_states()?;
 
         // Find word based on states and return lowest entropy words
-        let words: Vec<Word> = WFC::find_random_lowest_entropy_words(&self.states);
+        let mut words: Vec<Word> = WFC::find_random_lowest_entropy_words(&self.states, self.dictionary.as_ref(), &mut self.rng, |cell| {
+            let coords = (cell / self.cols, cell % self.cols);
+            let directional_sequences = self.board.get_sequences_from_position(coords.0, coords.1).unwrap_or_default();
+
+            WFC::words_for_acell(coords, directional_sequences, &self.directions, self.dictionary.as_ref(), &self.history, self.short_words)
+        })?;
+        if let Some(density) = self.overlap_density {
+            // Stable sort keeps the random order of candidates equally close to density
+            words.sort_by(|a, b| self.overlap_distance(a, density).total_cmp(&self.overlap_distance(b, density)));
+        }
+        trace!("backtracking step {} with {} lowest entropy words", step, words.len());
+        self.notify(GenerationEvent::Step { step, min_entropy: WFC::find_lowest_entropy(&self.states), candidates: words.len() });
 
-        for (idx, word) in words.iter().enumerate() {
+        for word in words.iter() {
             // For all lowest entropy words put first word on board
-            // FIXME We should check if a word is not already used
-            let history: Vec<String> = self.history.iter().map(|w|w.clone().word).collect();
-            if !history.contains(&word.word) {
-                self.board.put_word_on_board(word);
+            if self.history.iter().any(|w| w.word == word.word) {
+                continue;
             }
+            self.check_budget()?;
+            self.put_word(word);
 
-            if (self.board.is_board_populated()) {
-                return true;
+            if self.board.is_board_populated() {
+                return Ok(true);
             }
 
             // Remove necessary states
             self.invalidate_all_states();
 
             // Initiate states and recalculate entropies
-            self.calculate_possible_states();
+            self.calculate_possible_states()?;
 
             let min_entropy = WFC::find_lowest_entropy(&self.states);
-            /** If entropy is 0
+            /* If entropy is 0
                 - remove word from board
                 - if no more words are available go one recursive step back - return false
                 - continue in for loop
-            **/
-            if (min_entropy == 0) {
-                self.board.remove_word_from_board(word);
+            */
+            if min_entropy == 0 {
+                self.remove_word(word);
                 continue
             }
 
-            /** If entropy is >0
+            /* If entropy is >0
                 - go to next state (call this function again with new states as param)
-            **/
-            if self.backtrack(step + 1) {
-                return true
+            */
+            if self.backtrack(step + 1)? {
+                return Ok(true)
             }
 
             // If we are unsuccessfully at the end, remove and process
-            self.board.remove_word_from_board(word);
+            self.remove_word(word);
         }
 
 
 
         // in the end return true
-        return false;
+        return Ok(false);
+    }
+
+    fn overlap_distance(&self, word: &Word, density: f64) -> f64 {
+        let overlaps = self.board.count_overlaps(word) as f64 / word.word.chars().count() as f64;
+        (overlaps - density).abs()
+    }
+
+    fn put_word(&mut self, word: &Word) {
+        self.board.put_word_on_board(word);
+        self.history.push(word.clone());
+        self.placements += 1;
+
+        let cells_remaining = self.board.count_empty_cells();
+        if cells_remaining < self.best_remaining {
+            self.best_remaining = cells_remaining;
+            self.best = self.history.clone();
+        }
+        self.notify(GenerationEvent::WordPlaced { word, depth: self.history.len(), cells_remaining });
+    }
+
+    fn remove_word(&mut self, word: &Word) {
+        self.history.pop();
+        self.board.remove_word_from_board(word);
+        self.backtracks += 1;
+
+        let cells_remaining = self.board.count_empty_cells();
+        self.notify(GenerationEvent::WordRemoved { word, depth: self.history.len(), cells_remaining });
+    }
+
+    fn notify(&mut self, event: GenerationEvent) {
+        if let Some(observer) = self.observer.as_mut() {
+            observer.on_event(&event);
+        }
     }
 
     fn invalidate_all_states(&mut self) {
         for row in 0..self.rows {
             for col in 0..self.cols {
-                self.states.remove(&(row * self.rows + col));
+                self.states.remove(&(row * self.cols + col));
             }
         }
     }
 
+    #[allow(dead_code)]
     fn invalidate_required_states(&mut self, word: &Word) {
         // TODO : For each character in word (depth) calculate
         for depth in 0..word.word.len() {
@@ -149,17 +452,14 @@ impl Controller {
 
                 // TODO Reconsider !!!
                 // invalidate board.rows - row items or untill wall is touched
-                for idx in 0..max(self.rows, self.cols) {
+                for _ in 0..max(self.rows, self.cols) {
                     if row > 0 && col > 0 && row < self.rows as i32 && col < self.cols as i32 {
-                        let c = self.board.board[row as usize][col as usize];
-                        // println!("{}.{} r{}c{} = [{}]", idx, (row as usize * self.rows + col as usize), row, col, c);
-
-                        if (c == '*') {
+                        if self.board.is_solution_cell(row as usize, col as usize) {
                             break;
                         }
 
                         // invalidate states cache
-                        self.states.remove(&(row as usize * self.rows + col as usize));
+                        self.states.remove(&(row as usize * self.cols + col as usize));
                     } else {
                         // if row or col is outside the board let's go to the next one
                         break;
@@ -173,28 +473,27 @@ impl Controller {
         }
     }
 
-    fn calculate_possible_states(&mut self)  {
+    // A pass over a large board can take long, so the budget is checked before every cell which is searched
+    fn calculate_possible_states(&mut self) -> Result<(), CrosswordoError> {
         // For each row and col (each cell) traverse the position in all directions
-        for (rowIndex, row) in self.board.board.iter().enumerate() {
-            for (colIndex, col) in row.iter().enumerate() {
-                let mut words: Vec<Word> = Vec::new();
-
+        for row_index in 0..self.rows {
+            for col_index in 0..self.cols {
                 // Check states, if a word already has state do not process again, otherwise if states are invalidated get states
-                if let Some (cached) = self.states.get(&(rowIndex * self.rows + colIndex)) {
-                    words = cached.clone();
-                } else {
-                    let directional_sequences = self.board.get_sequences_from_position(rowIndex, colIndex).unwrap_or_else(HashMap::new);
-
-                    words = WFC::calculate_entropy_for_acell(
-                        rowIndex, colIndex, rowIndex * self.rows + colIndex,
-                        directional_sequences, &self.dictionary, self.history.iter().map(|w| w.word.clone()).collect(),
-                        &mut self.sequence_cache
+                if !self.states.contains_key(&(row_index * self.cols + col_index)) {
+                    self.check_budget()?;
+                    let directional_sequences = self.board.get_sequences_from_position(row_index, col_index).unwrap_or_default();
+
+                    let entropy = WFC::calculate_entropy_for_acell(
+                        directional_sequences, &self.directions, self.dictionary.as_ref(), &self.history,
+                        self.short_words, &mut self.sequence_cache
                     );
 
-                    self.states.insert((rowIndex * self.rows + colIndex), words.clone());
+                    self.states.insert(row_index * self.cols + col_index, entropy);
                 }
             }
         }
+
+        Ok(())
     }
 
 }
\ No newline at end of file
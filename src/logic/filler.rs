@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+use log::debug;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use crate::entities::board::Board;
+use crate::entities::dictionary::Dictionary;
+use crate::error::CrosswordoError;
+use crate::MAX;
+
+pub const ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz";
+
+// Random letters put into cells no word could cover, they are marked as filler on board and in puzzle
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filler {
+    alphabet: Vec<char>,
+    // Relative probability of each letter of alphabet
+    weights: Vec<f64>,
+}
+
+impl Filler {
+    // Letters of alphabet are picked with the same probability
+    pub fn new(alphabet: &str) -> Result<Self, CrosswordoError> {
+        let mut letters: Vec<char> = Vec::new();
+        for c in alphabet.chars().filter(|c| !c.is_whitespace()).flat_map(|c| c.to_lowercase()) {
+            if !c.is_alphabetic() {
+                return Err(CrosswordoError::InvalidFiller(format!("unsupported character '{}'", c)));
+            }
+            if !letters.contains(&c) {
+                letters.push(c);
+            }
+        }
+
+        if letters.is_empty() {
+            return Err(CrosswordoError::InvalidFiller(String::from("alphabet is empty")));
+        }
+
+        let weights = vec![1.0; letters.len()];
+        Ok(Filler { alphabet: letters, weights })
+    }
+
+    // Letters are picked as often as they appear in dictionary so the filler looks like the language
+    pub fn from_dictionary(dictionary: &[String]) -> Result<Self, CrosswordoError> {
+        // Sorted alphabet keeps the filler independent of dictionary order
+        let mut frequencies: BTreeMap<char, usize> = BTreeMap::new();
+        for c in dictionary.iter().flat_map(|word| word.chars()).filter(|c| c.is_alphabetic()).flat_map(|c| c.to_lowercase()) {
+            *frequencies.entry(c).or_insert(0) += 1;
+        }
+
+        if frequencies.is_empty() {
+            return Err(CrosswordoError::InvalidFiller(String::from("dictionary has no letters")));
+        }
+
+        Ok(Filler {
+            alphabet: frequencies.keys().copied().collect(),
+            weights: frequencies.values().map(|count| *count as f64).collect(),
+        })
+    }
+
+    pub fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    pub fn weight(&self, c: char) -> Option<f64> {
+        self.alphabet.iter().position(|letter| *letter == c).map(|idx| self.weights[idx])
+    }
+
+    // Fills every empty cell in reading order and returns the number of filled cells. Letters are tried in
+    // weighted random order and the first one which does not spell a dictionary word is used
+    pub fn fill<R: Rng>(&self, board: &mut Board, dictionary: &dyn Dictionary, rng: &mut R) -> usize {
+        let mut filled = 0;
+        for row in 0..board.rows {
+            for col in 0..board.cols {
+                if board.board[row][col] != '?' {
+                    continue;
+                }
+
+                let letters: Vec<char> = self.alphabet
+                    .iter()
+                    .zip(&self.weights)
+                    .collect::<Vec<(&char, &f64)>>()
+                    .choose_multiple_weighted(rng, self.alphabet.len(), |(_, weight)| **weight)
+                    .unwrap()
+                    .map(|(c, _)| **c)
+                    .collect();
+
+                let c = letters
+                    .iter()
+                    .find(|c| !Self::spells_word(board, dictionary, row, col, **c))
+                    .copied()
+                    .unwrap_or_else(|| {
+                        debug!("every filler letter spells a word at R{}C{}", row, col);
+                        letters[0]
+                    });
+
+                board.put_filler(row, col, c);
+                filled += 1;
+            }
+        }
+
+        filled
+    }
+
+    // Checks every sequence of letters passing the cell in the directions words are read on board
+    fn spells_word(board: &Board, dictionary: &dyn Dictionary, row: usize, col: usize, c: char) -> bool {
+        for direction in board.directions() {
+            // Letters before the cell are collected against the direction and reversed
+            let mut before: Vec<char> = Self::letters_from(board, row, col, -direction.getRow(), -direction.getCol());
+            before.reverse();
+            let after: Vec<char> = Self::letters_from(board, row, col, direction.getRow(), direction.getCol());
+
+            let line: Vec<char> = before.iter().chain([c].iter()).chain(after.iter()).copied().collect();
+            let idx = before.len();
+
+            for start in 0..=idx {
+                for end in (idx + 1)..=line.len() {
+                    if end - start >= MAX && dictionary.contains(&line[start..end].iter().collect::<String>()) {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    // Letters following the cell in a direction until an empty, solution or blocked cell or the edge of board
+    fn letters_from(board: &Board, row: usize, col: usize, row_step: i32, col_step: i32) -> Vec<char> {
+        let mut letters: Vec<char> = Vec::new();
+        let mut row = row as i32 + row_step;
+        let mut col = col as i32 + col_step;
+
+        while row >= 0 && col >= 0 && row < board.rows as i32 && col < board.cols as i32 {
+            let c = board.board[row as usize][col as usize];
+            if !c.is_alphabetic() {
+                break;
+            }
+
+            letters.push(c);
+            row += row_step;
+            col += col_step;
+        }
+
+        letters
+    }
+}
+
+impl Default for Filler {
+    fn default() -> Self {
+        Filler::new(ALPHABET).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::entities::direction::Direction;
+    use crate::entities::trie::Trie;
+    use crate::entities::word::Word;
+
+    #[test]
+    fn test_alphabet() {
+        let filler = Filler::new("X y Ž x").unwrap();
+
+        assert_eq!(filler.alphabet(), &['x', 'y', 'ž']);
+        assert!(matches!(Filler::new("ab1"), Err(CrosswordoError::InvalidFiller(_))));
+        assert!(matches!(Filler::new(" "), Err(CrosswordoError::InvalidFiller(_))));
+    }
+
+    #[test]
+    fn test_from_dictionary() {
+        let filler = Filler::from_dictionary(&[String::from("banana"), String::from("Cab")]).unwrap();
+
+        assert_eq!(filler.alphabet(), &['a', 'b', 'c', 'n']);
+        assert_eq!(filler.weight('a'), Some(4.0));
+        assert_eq!(filler.weight('n'), Some(2.0));
+        assert_eq!(filler.weight('z'), None);
+    }
+
+    #[test]
+    fn test_fill() {
+        let mut board = Board::new(4, 4).unwrap();
+        board.put_word_on_board(&Word::new(String::from("cold"), Direction::EAST(), (0, 0)));
+
+        let filled = Filler::new("xy").unwrap().fill(&mut board, &Trie::new(), &mut ChaCha8Rng::seed_from_u64(1));
+
+        assert_eq!(filled, 12);
+        assert!(board.is_board_populated());
+        assert_eq!(board.board[0], vec!['c', 'o', 'l', 'd']);
+        assert!(board.board[1..].iter().flatten().all(|c| *c == 'x' || *c == 'y'));
+        assert_eq!(board.filler_cells().len(), 12);
+    }
+
+    #[test]
+    fn test_fill_avoids_words() {
+        let mut dictionary = Trie::new();
+        dictionary.insert("aaa").unwrap();
+        dictionary.insert("bbb").unwrap();
+        let mut board = Board::new(4, 8).unwrap().with_directions(&[Direction::EAST()]);
+
+        Filler::new("ab").unwrap().fill(&mut board, &dictionary, &mut ChaCha8Rng::seed_from_u64(1));
+
+        for row in &board.board {
+            assert!(row.windows(3).all(|letters| letters != ['a', 'a', 'a'] && letters != ['b', 'b', 'b']));
+        }
+    }
+}
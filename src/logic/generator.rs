@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+use crate::entities::puzzle::Puzzle;
+use crate::entities::dictionary::{Dictionary, DictionaryBackend};
+use crate::entities::direction::Direction;
+use crate::entities::placement::PlacementStrategy;
+use crate::entities::template::Template;
+use crate::error::CrosswordoError;
+use crate::logic::budget::{Budget, CancellationToken};
+use crate::logic::config::GeneratorConfig;
+use crate::logic::controller::Controller;
+use crate::logic::filler::Filler;
+use crate::logic::observer::GenerationObserver;
+
+// Words with their frequencies loaded by the controller into the configured backend, or a dictionary built beforehand
+enum Source {
+    Words(Vec<String>, HashMap<String, f32>),
+    Loaded(Box<dyn Dictionary>),
+}
+
+// Entry point for embedding the generator, it collects the parameters and runs the controller
+pub struct PuzzleBuilder {
+    dictionary: Source,
+    config: GeneratorConfig,
+    observer: Option<Box<dyn GenerationObserver>>,
+}
+
+impl PuzzleBuilder {
+    pub fn new(dictionary: Vec<String>) -> Self {
+        PuzzleBuilder {
+            dictionary: Source::Words(dictionary, HashMap::new()),
+            config: GeneratorConfig::default(),
+            observer: None,
+        }
+    }
+
+    // Uses a loaded dictionary such as a compiled PackedTrie as it is, dictionary_backend has no effect
+    pub fn from_dictionary(dictionary: Box<dyn Dictionary>) -> Self {
+        PuzzleBuilder {
+            dictionary: Source::Loaded(dictionary),
+            config: GeneratorConfig::default(),
+            observer: None,
+        }
+    }
+
+    // Frequency or score of dictionary words, common words are placed more often. A loaded dictionary keeps its own
+    pub fn frequencies(mut self, frequencies: HashMap<String, f32>) -> Self {
+        if let Source::Words(_, current) = &mut self.dictionary {
+            *current = frequencies;
+        }
+        self
+    }
+
+    pub fn config(mut self, config: GeneratorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn size(mut self, rows: usize, cols: usize) -> Self {
+        self.config.rows = rows;
+        self.config.cols = cols;
+        self
+    }
+
+    pub fn solution(mut self, solution: &str) -> Self {
+        self.config.solution = solution.to_string();
+        self
+    }
+
+    // Spreads solution letters in equal clusters by default, e.g. PlacementStrategy::Bottom keeps them together
+    pub fn placement(mut self, strategy: PlacementStrategy) -> Self {
+        self.config.placement = strategy;
+        self
+    }
+
+    // Packed dictionary takes a fraction of the memory and finds the same words
+    pub fn dictionary_backend(mut self, backend: DictionaryBackend) -> Self {
+        self.config.dictionary_backend = backend;
+        self
+    }
+
+    // Same seed, dictionary and parameters always produce the same puzzle, random seed is used when not set
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    // Children puzzles use only EAST and SOUTH, Direction::FORWARD() avoids backwards words
+    pub fn directions(mut self, directions: &[Direction]) -> Self {
+        self.config.directions = directions.to_vec();
+        self
+    }
+
+    // Generation starts from template letters and blocked cells, board takes the template size
+    pub fn template(mut self, template: Template) -> Self {
+        self.config.template = Some(template);
+        self
+    }
+
+    // Theme words guaranteed to appear on board, the rest is filled from dictionary
+    pub fn required_words(mut self, words: &[String]) -> Self {
+        self.config.required_words = words.to_vec();
+        self
+    }
+
+    // Generation fails with BudgetExhausted once any of the limits is reached
+    pub fn budget(mut self, budget: Budget) -> Self {
+        self.config.budget = budget;
+        self
+    }
+
+    // Token can be cancelled from another thread while generate is running
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.config.cancellation = Some(token);
+        self
+    }
+
+    // Board with the most covered cells is returned when the generation fails or runs out of budget
+    pub fn allow_partial(mut self, allow_partial: bool) -> Self {
+        self.config.allow_partial = allow_partial;
+        self
+    }
+
+    // Cells left empty are filled with random letters of filler alphabet
+    pub fn filler(mut self, filler: Filler) -> Self {
+        self.config.filler = Some(filler);
+        self
+    }
+
+    // Generation fails with DuplicateWords when a placed word can be found on board more than once
+    pub fn reject_duplicates(mut self, reject_duplicates: bool) -> Self {
+        self.config.reject_duplicates = reject_duplicates;
+        self
+    }
+
+    // Words can share letters with other words, enabled by default
+    pub fn crossings(mut self, crossings: bool) -> Self {
+        self.config.crossings = crossings;
+        self
+    }
+
+    // Solution cells are never used by words, enabled by default
+    pub fn strict_solution(mut self, strict_solution: bool) -> Self {
+        self.config.strict_solution = strict_solution;
+        self
+    }
+
+    // Preferred share of word letters crossing other words, 0.0 avoids overlaps and 1.0 seeks them
+    pub fn overlap_density(mut self, density: f64) -> Self {
+        self.config.overlap_density = Some(density);
+        self
+    }
+
+    // Words can be shorter than the free sequence of cells they start, disabled by default
+    pub fn short_words(mut self, short_words: bool) -> Self {
+        self.config.short_words = short_words;
+        self
+    }
+
+    // Receives every placed and removed word and each step of backtracking, e.g. to drive a progress bar
+    pub fn observer<O: GenerationObserver + 'static>(mut self, observer: O) -> Self {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    // Prepares the controller with solution already placed on board without running the backtracking
+    pub fn build(self) -> Result<Controller, CrosswordoError> {
+        let mut controller = match self.dictionary {
+            Source::Words(words, frequencies) => Controller::new(&self.config, words, &frequencies)?,
+            Source::Loaded(dictionary) => Controller::with_dictionary(&self.config, dictionary)?,
+        };
+        if let Some(observer) = self.observer {
+            controller.set_observer(observer);
+        }
+
+        Ok(controller)
+    }
+
+    pub fn generate(self) -> Result<Puzzle, CrosswordoError> {
+        let mut controller = self.build()?;
+        controller.generate()?;
+
+        Ok(controller.puzzle())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use crate::entities::packed_trie::PackedTrie;
+    use crate::logic::observer::GenerationEvent;
+    use crate::logic::solver::solve;
+    use crate::test_utils::en_dictionary;
+
+    #[test]
+    fn test_same_seed_same_puzzle() {
+        let first = PuzzleBuilder::new(en_dictionary()).size(4, 4).solution("ab").seed(3).generate().unwrap();
+        let second = PuzzleBuilder::new(en_dictionary()).size(4, 4).solution("ab").seed(3).generate().unwrap();
+
+        assert_eq!(first.grid, second.grid);
+        assert_eq!(first.words, second.words);
+        assert_eq!(first.solution_cells, second.solution_cells);
+        assert_eq!(first.seed, 3);
+    }
+
+    #[test]
+    fn test_hidden_message() {
+        let puzzle = PuzzleBuilder::new(en_dictionary()).size(4, 4).solution("ab").seed(3).generate().unwrap();
+        let words: Vec<String> = puzzle.words.iter().map(|word| word.word.clone()).collect();
+
+        assert!(puzzle.grid.iter().flatten().all(|c| c.is_alphabetic()));
+        assert_eq!(solve(&puzzle.grid, &words, &Direction::ALL()).hidden_message, "ab");
+    }
+
+    #[test]
+    fn test_no_crossings() {
+        let puzzle = PuzzleBuilder::new(en_dictionary())
+            .size(4, 4).solution("ab").seed(3)
+            .crossings(false)
+            .budget(Budget { max_backtracks: Some(100), ..Budget::default() })
+            .allow_partial(true)
+            .generate()
+            .unwrap();
+
+        assert_eq!(puzzle.overlaps, 0);
+    }
+
+    #[test]
+    fn test_short_words() {
+        // Without short words every word runs up to the edge of board or a solution cell
+        let ends_early = |puzzle: &Puzzle| puzzle.words.iter().any(|word| {
+            let (row, col) = *word.cells().last().unwrap();
+            let next = (row as i32 + word.direction.getRow(), col as i32 + word.direction.getCol());
+            (0..5).contains(&next.0) && (0..5).contains(&next.1)
+                && !puzzle.solution_cells.contains(&(next.0 as usize, next.1 as usize))
+        });
+
+        let full = PuzzleBuilder::new(en_dictionary()).size(5, 5).solution("ab").seed(1).generate().unwrap();
+        let short = PuzzleBuilder::new(en_dictionary()).size(5, 5).solution("ab").seed(1).short_words(true).generate().unwrap();
+
+        assert!(!ends_early(&full));
+        assert!(ends_early(&short));
+    }
+
+    #[test]
+    fn test_placement() {
+        let puzzle = PuzzleBuilder::new(en_dictionary()).size(4, 4).solution("ab").seed(3).placement(PlacementStrategy::Bottom).generate().unwrap();
+
+        assert_eq!(puzzle.solution_cells, vec![(3, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn test_packed_dictionary() {
+        let trie = PuzzleBuilder::new(en_dictionary()).size(4, 4).solution("ab").seed(3).generate().unwrap();
+        let packed = PuzzleBuilder::new(en_dictionary()).size(4, 4).solution("ab").seed(3).dictionary_backend(DictionaryBackend::Packed).generate().unwrap();
+
+        assert_eq!(trie, packed);
+    }
+
+    #[test]
+    fn test_short_words_are_skipped() {
+        let mut words = en_dictionary();
+        words.push(String::from("čo"));
+
+        for backend in [DictionaryBackend::Trie, DictionaryBackend::Packed] {
+            assert!(PuzzleBuilder::new(words.clone()).size(4, 4).solution("ab").seed(3).dictionary_backend(backend).build().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_frequencies() {
+        // Shorter words are more common
+        let frequencies: HashMap<String, f32> = en_dictionary().into_iter().map(|word| (word.clone(), 1.0 / word.len() as f32)).collect();
+        let trie = PuzzleBuilder::new(en_dictionary()).frequencies(frequencies.clone()).size(4, 4).solution("ab").seed(3).generate().unwrap();
+        let packed = PuzzleBuilder::new(en_dictionary()).frequencies(frequencies).size(4, 4).solution("ab").seed(3).dictionary_backend(DictionaryBackend::Packed).generate().unwrap();
+
+        assert_eq!(trie, packed);
+        assert!(!trie.grid.iter().flatten().any(|c| *c == '?'));
+    }
+
+    #[test]
+    fn test_compiled_dictionary() {
+        let compiled = PackedTrie::from_image(PackedTrie::new(&en_dictionary()).unwrap().image().to_vec()).unwrap();
+        let words = PuzzleBuilder::new(en_dictionary()).size(4, 4).solution("ab").seed(3).generate().unwrap();
+        let loaded = PuzzleBuilder::from_dictionary(Box::new(compiled)).size(4, 4).solution("ab").seed(3).generate().unwrap();
+
+        assert_eq!(words, loaded);
+    }
+
+    #[test]
+    fn test_allowed_directions() {
+        let directions = [Direction::EAST(), Direction::SOUTH()];
+        let puzzle = PuzzleBuilder::new(en_dictionary()).size(4, 4).solution("ab").seed(1).directions(&directions).generate().unwrap();
+
+        assert!(puzzle.words.iter().all(|word| directions.contains(&word.direction)));
+    }
+
+    #[test]
+    fn test_template() {
+        let template = Template::parse("??b?\n?#??\n??k?\n????").unwrap();
+        let puzzle = PuzzleBuilder::new(en_dictionary()).solution("ab").seed(1).template(template).generate().unwrap();
+
+        assert_eq!((puzzle.rows, puzzle.cols), (4, 4));
+        assert_eq!(puzzle.grid[0][2], 'b');
+        assert_eq!(puzzle.grid[1][1], '#');
+        assert_eq!(puzzle.grid[2][2], 'k');
+    }
+
+    #[test]
+    fn test_template_letters_are_covered() {
+        let template = Template::parse("?????\n?bike\n?????\n?????\n?????").unwrap();
+        let puzzle = PuzzleBuilder::new(en_dictionary()).solution("ab").seed(2).template(template).generate().unwrap();
+        let words: Vec<String> = puzzle.words.iter().map(|word| word.word.clone()).collect();
+
+        assert_eq!(&puzzle.grid[1][1..], &['b', 'i', 'k', 'e']);
+        assert_eq!(solve(&puzzle.grid, &words, &Direction::ALL()).hidden_message, "ab");
+    }
+
+    #[test]
+    fn test_required_words() {
+        let required = vec![String::from("bird"), String::from("cat")];
+        let puzzle = PuzzleBuilder::new(en_dictionary()).size(5, 5).solution("ab").seed(1).required_words(&required).generate().unwrap();
+
+        for word in required {
+            assert!(puzzle.words.iter().any(|placed| placed.word == word));
+        }
+    }
+
+    #[test]
+    fn test_required_words_are_rearranged() {
+        // Only the second row leaves room for wxyz, abc is tried in the first row before
+        let placed: Arc<Mutex<Vec<(usize, usize)>>> = Arc::new(Mutex::new(Vec::new()));
+        let collected = Arc::clone(&placed);
+
+        let puzzle = PuzzleBuilder::new(vec![String::from("abc"), String::from("wxyz")])
+            .template(Template::parse("????\n????\n####\n####").unwrap())
+            .solution("x").seed(0).placement(PlacementStrategy::Bottom).directions(&[Direction::EAST()])
+            .required_words(&[String::from("abc")])
+            .observer(move |event: &GenerationEvent| match event {
+                GenerationEvent::WordPlaced { word, .. } if word.word == "abc" => collected.lock().unwrap().push(word.coords),
+                _ => {}
+            })
+            .generate()
+            .unwrap();
+
+        assert_eq!(puzzle.grid[0], vec!['w', 'x', 'y', 'z']);
+        assert_eq!(puzzle.grid[1], vec!['x', 'a', 'b', 'c']);
+        assert_eq!(*placed.lock().unwrap(), vec![(0, 0), (0, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn test_required_words_not_placed() {
+        let required = vec![String::from("cat"), String::from("elephant")];
+        let result = PuzzleBuilder::new(en_dictionary()).size(4, 4).solution("ab").seed(1).required_words(&required).generate();
+
+        assert_eq!(result.err(), Some(CrosswordoError::RequiredWordsNotPlaced(vec![String::from("elephant"), String::from("cat")])));
+    }
+
+    #[test]
+    fn test_observer_events() {
+        let events: Arc<Mutex<Vec<(bool, usize, usize)>>> = Arc::new(Mutex::new(Vec::new()));
+        let collected = Arc::clone(&events);
+
+        let puzzle = PuzzleBuilder::new(en_dictionary())
+            .size(4, 4).solution("ab").seed(3)
+            .observer(move |event: &GenerationEvent| match event {
+                GenerationEvent::WordPlaced { depth, cells_remaining, .. } => collected.lock().unwrap().push((true, *depth, *cells_remaining)),
+                GenerationEvent::WordRemoved { depth, cells_remaining, .. } => collected.lock().unwrap().push((false, *depth, *cells_remaining)),
+                GenerationEvent::Step { .. } => {}
+            })
+            .generate()
+            .unwrap();
+
+        let events = events.lock().unwrap();
+        let placed = events.iter().filter(|(placed, _, _)| *placed).count();
+        assert_eq!(placed - (events.len() - placed), puzzle.words.len());
+        assert_eq!(events.last(), Some(&(true, puzzle.words.len(), 0)));
+    }
+
+    #[test]
+    fn test_budget_keeps_partial_board() {
+        let budget = Budget { max_placements: Some(2), ..Budget::default() };
+        let mut controller = PuzzleBuilder::new(en_dictionary()).size(5, 5).solution("ab").seed(1).budget(budget).build().unwrap();
+
+        assert_eq!(controller.generate(), Err(CrosswordoError::BudgetExhausted(String::from("2 placements"))));
+        assert!(!controller.placed_words().is_empty());
+        assert!(!controller.board().is_board_populated());
+    }
+
+    #[test]
+    fn test_time_limit_with_required_words() {
+        let required: Vec<String> = ["house", "table", "chair", "mouse", "horse", "plant", "water", "light", "stone", "bread", "apple", "green", "night"]
+            .iter().map(|word| word.to_string()).collect();
+        let budget = Budget { time_limit: Some(Duration::from_millis(20)), ..Budget::default() };
+        let mut controller = PuzzleBuilder::new(en_dictionary()).size(6, 6).solution("ab").seed(1).required_words(&required).budget(budget).build().unwrap();
+
+        let started = Instant::now();
+        assert_eq!(controller.generate(), Err(CrosswordoError::BudgetExhausted(String::from("time limit of 20 ms"))));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_partial_while_placing_required_words() {
+        let required = vec![String::from("house"), String::from("table")];
+        let budget = Budget { max_placements: Some(1), ..Budget::default() };
+        let mut controller = PuzzleBuilder::new(en_dictionary())
+            .size(5, 5).solution("ab").seed(1)
+            .required_words(&required).budget(budget).allow_partial(true)
+            .build().unwrap();
+
+        assert_eq!(controller.generate(), Ok(()));
+        assert_eq!(controller.placed_words().iter().map(|word| word.word.as_str()).collect::<Vec<&str>>(), vec!["house"]);
+        assert!(!controller.board().is_board_populated());
+    }
+
+    #[test]
+    fn test_partial_without_all_required_words() {
+        // Three free cells of the first row fit only one of the words
+        let mut controller = PuzzleBuilder::new(vec![String::from("abc"), String::from("xyz")])
+            .template(Template::parse("????\n####\n####\n####").unwrap())
+            .solution("x").seed(0).placement(PlacementStrategy::Bottom).directions(&[Direction::EAST()])
+            .required_words(&[String::from("abc"), String::from("xyz")]).allow_partial(true)
+            .build().unwrap();
+
+        assert_eq!(controller.generate(), Ok(()));
+        assert_eq!(controller.placed_words().iter().map(|word| word.word.as_str()).collect::<Vec<&str>>(), vec!["abc"]);
+    }
+
+    #[test]
+    fn test_partial_with_filler() {
+        let budget = Budget { max_placements: Some(2), ..Budget::default() };
+        let puzzle = PuzzleBuilder::new(en_dictionary())
+            .size(5, 5).solution("ab").seed(1)
+            .budget(budget)
+            .allow_partial(true)
+            .filler(Filler::new("xyz").unwrap())
+            .generate()
+            .unwrap();
+
+        assert!(!puzzle.words.is_empty());
+        assert!(!puzzle.filler_cells.is_empty());
+        assert!(puzzle.grid.iter().flatten().all(|c| *c != '?'));
+        assert!(puzzle.filler_cells.iter().all(|(row, col)| "xyz".contains(puzzle.grid[*row][*col])));
+    }
+
+    #[test]
+    fn test_cancelled() {
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let result = PuzzleBuilder::new(en_dictionary()).size(4, 4).solution("ab").seed(3).cancellation(token).generate();
+
+        assert_eq!(result.err(), Some(CrosswordoError::Cancelled));
+    }
+
+    #[test]
+    fn test_no_directions() {
+        let result = PuzzleBuilder::new(en_dictionary()).size(4, 4).solution("ab").directions(&[Direction::CENTER()]).generate();
+
+        assert_eq!(result.err(), Some(CrosswordoError::NoDirections));
+    }
+}
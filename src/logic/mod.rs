@@ -1,2 +1,9 @@
+pub mod budget;
+pub mod config;
 pub mod controller;
-mod wfc;
\ No newline at end of file
+pub mod filler;
+pub mod generator;
+pub mod observer;
+pub mod solver;
+pub mod verifier;
+pub mod wfc;
@@ -0,0 +1,21 @@
+use crate::entities::word::Word;
+
+// Progress of generation reported to an observer, depth is the number of words on board after the event
+#[derive(Debug)]
+pub enum GenerationEvent<'a> {
+    WordPlaced { word: &'a Word, depth: usize, cells_remaining: usize },
+    WordRemoved { word: &'a Word, depth: usize, cells_remaining: usize },
+    // Backtracking entered a new step with candidates of the lowest entropy
+    Step { step: usize, min_entropy: usize, candidates: usize },
+}
+
+pub trait GenerationObserver: Send {
+    fn on_event(&mut self, event: &GenerationEvent);
+}
+
+// Closures can be used directly as observers
+impl<F: FnMut(&GenerationEvent) + Send> GenerationObserver for F {
+    fn on_event(&mut self, event: &GenerationEvent) {
+        self(event)
+    }
+}
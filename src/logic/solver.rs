@@ -0,0 +1,197 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use crate::entities::direction::Direction;
+use crate::entities::template::BLOCKED;
+use crate::entities::word::Word;
+use crate::error::CrosswordoError;
+
+// Result of checking a grid against its word list
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolverReport {
+    // Words found exactly once in the order of word list
+    pub found: Vec<Word>,
+    pub missing: Vec<String>,
+    // Words which can be found at more than one position with all their positions
+    pub ambiguous: Vec<(String, Vec<Word>)>,
+    // Letters not covered by any found word in reading order, solution is placed on board the same way
+    pub hidden_message: String,
+}
+
+impl SolverReport {
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty() && self.ambiguous.is_empty()
+    }
+}
+
+impl fmt::Display for SolverReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for word in &self.found {
+            writeln!(f, "{} at R{}C{} {}", word.word, word.coords.0, word.coords.1, word.direction.getName())?;
+        }
+        for word in &self.missing {
+            writeln!(f, "{} is missing", word)?;
+        }
+        for (word, occurrences) in &self.ambiguous {
+            let positions: Vec<String> = occurrences
+                .iter()
+                .map(|occurrence| format!("R{}C{} {}", occurrence.coords.0, occurrence.coords.1, occurrence.direction.getName()))
+                .collect();
+            writeln!(f, "{} is ambiguous at {}", word, positions.join(", "))?;
+        }
+
+        write!(f, "hidden message: {}", self.hidden_message)
+    }
+}
+
+// Each line is a row of letters, whitespaces between cells are ignored and '#' marks a blocked cell
+pub fn parse_grid(input: &str) -> Result<Vec<Vec<char>>, CrosswordoError> {
+    let grid: Vec<Vec<char>> = input
+        .lines()
+        .map(|line| line.chars().filter(|c| !c.is_whitespace()).flat_map(|c| c.to_lowercase()).collect::<Vec<char>>())
+        .filter(|row| !row.is_empty())
+        .collect();
+
+    let cols = grid.first().map(|row| row.len()).unwrap_or(0);
+    if cols == 0 {
+        return Err(CrosswordoError::InvalidGrid(String::from("grid is empty")));
+    }
+    if grid.iter().any(|row| row.len() != cols) {
+        return Err(CrosswordoError::InvalidGrid(String::from("all rows need to have the same length")));
+    }
+    if let Some(c) = grid.iter().flatten().find(|c| !(c.is_alphabetic() || **c == BLOCKED)) {
+        return Err(CrosswordoError::InvalidGrid(format!("unsupported character '{}'", c)));
+    }
+
+    Ok(grid)
+}
+
+// Word per line, letters are kept as they are so words with diacritics are found in the same grid
+pub fn parse_words(input: &str) -> Vec<String> {
+    input
+        .lines()
+        .map(|word| word.trim().to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+// Locates every word of the list in grid and reads the letters left over
+pub fn solve(grid: &[Vec<char>], words: &[String], directions: &[Direction]) -> SolverReport {
+    let mut report = SolverReport::default();
+    let mut covered: BTreeSet<(usize, usize)> = BTreeSet::new();
+
+    for word in words.iter().map(|word| word.trim().to_lowercase()).filter(|word| !word.is_empty()) {
+        let mut occurrences: Vec<Word> = Vec::new();
+        for occurrence in find_occurrences(grid, &word, directions) {
+            // Palindromes read backwards cover the same cells and are the same occurrence
+            let cells: BTreeSet<(usize, usize)> = occurrence.cells().into_iter().collect();
+            if !occurrences.iter().any(|other| other.cells().into_iter().collect::<BTreeSet<(usize, usize)>>() == cells) {
+                occurrences.push(occurrence);
+            }
+        }
+
+        match occurrences.len() {
+            0 => report.missing.push(word),
+            1 => {
+                covered.extend(occurrences[0].cells());
+                report.found.push(occurrences.remove(0));
+            }
+            _ => report.ambiguous.push((word, occurrences)),
+        }
+    }
+
+    for (row, line) in grid.iter().enumerate() {
+        for (col, c) in line.iter().enumerate() {
+            if *c != BLOCKED && !covered.contains(&(row, col)) {
+                report.hidden_message.push(*c);
+            }
+        }
+    }
+
+    report
+}
+
+fn find_occurrences(grid: &[Vec<char>], word: &str, directions: &[Direction]) -> Vec<Word> {
+    let letters: Vec<char> = word.chars().collect();
+    let mut occurrences: Vec<Word> = Vec::new();
+
+    for row in 0..grid.len() {
+        for col in 0..grid[row].len() {
+            for direction in directions.iter().filter(|direction| **direction != Direction::CENTER()) {
+                let matches = letters.iter().enumerate().all(|(depth, c)| {
+                    let row = row as i32 + direction.getRow() * depth as i32;
+                    let col = col as i32 + direction.getCol() * depth as i32;
+
+                    row >= 0 && col >= 0 && (row as usize) < grid.len() && (col as usize) < grid[row as usize].len()
+                        && grid[row as usize][col as usize] == *c
+                });
+
+                if matches {
+                    occurrences.push(Word::new(word.to_string(), *direction, (row, col)));
+                }
+            }
+        }
+    }
+
+    occurrences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::words;
+
+    #[test]
+    fn test_parse_grid() {
+        let grid = parse_grid("C o l d\nA # r e\n\n").unwrap();
+
+        assert_eq!(grid, vec![vec!['c', 'o', 'l', 'd'], vec!['a', '#', 'r', 'e']]);
+        assert!(matches!(parse_grid("abc\nab"), Err(CrosswordoError::InvalidGrid(_))));
+        assert!(matches!(parse_grid("ab?"), Err(CrosswordoError::InvalidGrid(_))));
+        assert!(matches!(parse_grid(" \n"), Err(CrosswordoError::InvalidGrid(_))));
+    }
+
+    #[test]
+    fn test_solve_diacritics() {
+        let grid = parse_grid("čaj\nťxo\nšíp").unwrap();
+        let report = solve(&grid, &parse_words("Čaj\n\nšíp\n"), &Direction::ALL());
+
+        assert!(report.missing.is_empty());
+        assert_eq!(report.found.len(), 2);
+        assert_eq!(report.hidden_message, "ťxo");
+    }
+
+    #[test]
+    fn test_solve() {
+        let grid = parse_grid("cold\nahis\ntree\nsunb").unwrap();
+        let report = solve(&grid, &words(&["cold", "cats", "TREE", "sun", "owl"]), &Direction::ALL());
+
+        assert_eq!(report.found, vec![
+            Word::new(String::from("cold"), Direction::EAST(), (0, 0)),
+            Word::new(String::from("cats"), Direction::SOUTH(), (0, 0)),
+            Word::new(String::from("tree"), Direction::EAST(), (2, 0)),
+            Word::new(String::from("sun"), Direction::EAST(), (3, 0)),
+        ]);
+        assert_eq!(report.missing, vec!["owl"]);
+        assert!(!report.is_valid());
+        assert_eq!(report.hidden_message, "hisb");
+    }
+
+    #[test]
+    fn test_ambiguous() {
+        let grid = parse_grid("cold\nxxxx\ndloc\n#xxx").unwrap();
+        let report = solve(&grid, &words(&["cold"]), &Direction::ALL());
+
+        assert_eq!(report.ambiguous.len(), 1);
+        assert_eq!(report.ambiguous[0].1.len(), 2);
+        // Ambiguous words do not cover any cells
+        assert_eq!(report.hidden_message.len(), 15);
+    }
+
+    #[test]
+    fn test_palindrome_and_directions() {
+        let grid = parse_grid("level\nxxxxx\nxxxxx\nxxxxx").unwrap();
+
+        assert!(solve(&grid, &words(&["level"]), &Direction::ALL()).is_valid());
+        assert_eq!(solve(&grid, &words(&["level"]), &[Direction::SOUTH()]).missing, vec!["level"]);
+    }
+}
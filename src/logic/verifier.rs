@@ -0,0 +1,139 @@
+use std::collections::BTreeSet;
+use crate::entities::board::Board;
+use crate::entities::direction::Direction;
+use crate::entities::dictionary::Dictionary;
+use crate::entities::word::Word;
+use crate::MAX;
+
+// Dictionary words found on a finished board compared with the words which were placed
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Verification {
+    // Every dictionary word readable on board, placed words included
+    pub occurrences: Vec<Word>,
+    // Placed words which can be read also at another position, the puzzle is ambiguous
+    pub duplicates: Vec<Word>,
+    // Dictionary words readable on board which were never placed, e.g. spelled by crossings or filler
+    pub unintended: Vec<Word>,
+}
+
+impl Verification {
+    pub fn is_ambiguous(&self) -> bool {
+        !self.duplicates.is_empty()
+    }
+
+    // Texts of duplicated words sorted and without repetitions
+    pub fn duplicate_words(&self) -> Vec<String> {
+        self.duplicates.iter().map(|word| word.word.clone()).collect::<BTreeSet<String>>().into_iter().collect()
+    }
+}
+
+// Scans every line of board in its allowed directions and looks up all embedded words in dictionary
+pub fn verify(board: &Board, dictionary: &dyn Dictionary, placed: &[Word]) -> Verification {
+    let placed_cells: Vec<(String, BTreeSet<(usize, usize)>)> = placed
+        .iter()
+        .map(|word| (word.word.clone(), word.cells().into_iter().collect()))
+        .collect();
+
+    let mut verification = Verification::default();
+    for row in 0..board.rows {
+        for col in 0..board.cols {
+            for direction in board.directions() {
+                for word in words_from(board, dictionary, row, col, *direction) {
+                    let cells: BTreeSet<(usize, usize)> = word.cells().into_iter().collect();
+
+                    // Placed word itself, palindromes read backwards cover the same cells
+                    if placed_cells.iter().any(|(text, placed)| *text == word.word && *placed == cells) {
+                        verification.occurrences.push(word);
+                        continue;
+                    }
+
+                    if placed_cells.iter().any(|(text, _)| *text == word.word) {
+                        verification.duplicates.push(word.clone());
+                    } else {
+                        verification.unintended.push(word.clone());
+                    }
+                    verification.occurrences.push(word);
+                }
+            }
+        }
+    }
+
+    verification
+}
+
+// Dictionary words starting at the position, letters are read until a cell without a letter or the edge of board
+fn words_from(board: &Board, dictionary: &dyn Dictionary, row: usize, col: usize, direction: Direction) -> Vec<Word> {
+    let mut words: Vec<Word> = Vec::new();
+    let mut sequence = String::new();
+    let (mut current_row, mut current_col) = (row as i32, col as i32);
+
+    while current_row >= 0 && current_col >= 0 && current_row < board.rows as i32 && current_col < board.cols as i32 {
+        let c = board.board[current_row as usize][current_col as usize];
+        if !c.is_alphabetic() {
+            break;
+        }
+
+        sequence.push(c);
+        if sequence.chars().count() >= MAX && dictionary.contains(&sequence) {
+            words.push(Word::new(sequence.clone(), direction, (row, col)));
+        }
+
+        current_row += direction.getRow();
+        current_col += direction.getCol();
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::trie;
+
+    #[test]
+    fn test_clean_board() {
+        let mut board = Board::new(4, 4).unwrap().with_directions(&[Direction::EAST(), Direction::SOUTH()]);
+        let placed = vec![Word::new(String::from("cold"), Direction::EAST(), (0, 0))];
+        board.put_word_on_board(&placed[0]);
+
+        let verification = verify(&board, &trie(&["cold", "old"]), &placed);
+
+        assert!(!verification.is_ambiguous());
+        assert_eq!(verification.unintended, vec![Word::new(String::from("old"), Direction::EAST(), (0, 1))]);
+        assert_eq!(verification.occurrences.len(), 2);
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let mut board = Board::new(4, 4).unwrap();
+        let placed = vec![
+            Word::new(String::from("cold"), Direction::EAST(), (0, 0)),
+            Word::new(String::from("clod"), Direction::SOUTH(), (0, 0)),
+        ];
+        for word in &placed {
+            board.put_word_on_board(word);
+        }
+        board.put_filler(3, 1, 'o');
+        board.put_filler(3, 2, 'l');
+        board.put_filler(3, 3, 'c');
+
+        // Last row reads "dolc", backwards it spells "clod" again
+        let verification = verify(&board, &trie(&["cold", "clod"]), &placed);
+
+        assert!(verification.is_ambiguous());
+        assert_eq!(verification.duplicates, vec![Word::new(String::from("clod"), Direction::WEST(), (3, 3))]);
+        assert_eq!(verification.duplicate_words(), vec!["clod"]);
+    }
+
+    #[test]
+    fn test_palindrome_is_not_duplicate() {
+        let mut board = Board::new(4, 5).unwrap();
+        let placed = vec![Word::new(String::from("level"), Direction::EAST(), (0, 0))];
+        board.put_word_on_board(&placed[0]);
+
+        let verification = verify(&board, &trie(&["level"]), &placed);
+
+        assert!(!verification.is_ambiguous());
+        assert_eq!(verification.occurrences.len(), 2);
+    }
+}
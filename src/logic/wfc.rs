@@ -1,10 +1,11 @@
-use std::collections::{BTreeMap, HashMap, HashSet};
-use std::time::SystemTime;
+use std::collections::{BTreeMap, HashMap};
 use crate::entities::direction::Direction;
-use crate::entities::trie::Trie;
+use crate::entities::dictionary::Dictionary;
+use crate::entities::pattern::Pattern;
 use crate::entities::word::Word;
+use crate::error::CrosswordoError;
+use rand::Rng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
 use crate::MAX;
 
 pub struct WFC {
@@ -14,35 +15,30 @@ pub struct WFC {
 impl WFC {
 
     pub fn find_lowest_entropy(
-        states: &HashMap<usize, Vec<Word>>
+        states: &BTreeMap<usize, usize>
     ) -> usize {
-        let mut new_words = Vec::new();
-
-        let words = states
-            .iter()
-            .filter(|(_, words)| {
-                new_words.push(words.clone());
-                return !words.is_empty()
-            });
-
-        let entropy_min = words
-            .map(|(_, words)| {
-                words.len()
-            })
+        let entropy_min = states
+            .values()
+            .filter(|entropy| **entropy > 0)
             .min()
+            .copied()
             .unwrap_or(0);
 
         // println!("entropy {}", entropy_min);
         return entropy_min;
     }
 
-    pub fn find_random_lowest_entropy_words(
-        states: &HashMap<usize, Vec<Word>>
-    ) -> Vec<Word> {
+    // Words are searched only for the cells with the lowest entropy, words_for_cell gets the index of such a cell
+    pub fn find_random_lowest_entropy_words<R: Rng, F: FnMut(usize) -> Vec<Word>>(
+        states: &BTreeMap<usize, usize>,
+        dictionary: &dyn Dictionary,
+        rng: &mut R,
+        mut words_for_cell: F,
+    ) -> Result<Vec<Word>, CrosswordoError> {
         let entropy_min = WFC::find_lowest_entropy(states);
 
-        if (entropy_min == 0) {
-            panic!("Cannot be initiated")
+        if entropy_min == 0 {
+            return Err(CrosswordoError::NoAvailableWords);
         }
         // TODO : Add weight to each direction or calculate weights based on all positions, open position and words in certain direction
 
@@ -51,51 +47,100 @@ impl WFC {
 
         states
             .iter()
-            .filter(|(_, words)| words.len() == entropy_min)
-            .for_each(|(_, words)| saved_words.extend(words.clone()));
+            .filter(|(_, entropy)| **entropy == entropy_min)
+            .for_each(|(cell, _)| saved_words.extend(words_for_cell(*cell)));
+
+        // Common words are more likely to be tried first, words without frequency count as the least common candidate
+        let frequencies: Vec<Option<f32>> = saved_words.iter().map(|word| dictionary.frequency(&word.word)).collect();
+        let Some(least) = frequencies.iter().flatten().copied().reduce(f32::min) else {
+            let random_words: Vec<Word> = saved_words
+                .choose_multiple(rng, saved_words.len())
+                .cloned().collect::<Vec<Word>>();
+
+            return Ok(random_words);
+        };
+
+        // Frequencies are checked to be finite and non-negative when they are loaded
+        let weighted: Vec<(Word, f32)> = saved_words
+            .into_iter()
+            .zip(frequencies)
+            .map(|(word, frequency)| (word, frequency.unwrap_or(least)))
+            .collect();
+        let random_words: Vec<Word> = weighted
+            .choose_multiple_weighted(rng, weighted.len(), |(_, frequency)| *frequency)
+            .unwrap()
+            .map(|(word, _)| word.clone())
+            .collect();
+
+        return Ok(random_words);
+    }
 
-        let random_words: Vec<Word> = saved_words
-            .choose_multiple(&mut thread_rng(), saved_words.len())
-            .cloned().collect::<Vec<Word>>();
+    // Number of words which can start at the cell, matches are only counted so nothing is allocated for them
+    pub fn calculate_entropy_for_acell(
+        directional_sequences: BTreeMap<Direction, String>,
+        directions: &[Direction],
+        dictionary: &dyn Dictionary,
+        used: &[Word],
+        short_words: bool,
+        sequence_cache: &mut HashMap<String, usize>,
+    ) -> usize {
+        let mut entropy = 0;
+
+        // From default sequence we might have
+        for (direction, sequence) in directional_sequences {
+            // for sequences lower than MAX or in not allowed directions we won't compile
+            if sequence.chars().count() >= MAX && directions.contains(&direction) {
+                let pattern = WFC::sequence_pattern(&sequence, short_words);
+                let count = *sequence_cache
+                    .entry(sequence)
+                    .or_insert_with(|| dictionary.count_matches(&pattern));
+                if count == 0 {
+                    continue;
+                }
+
+                // Words already on board are not placed again
+                let placed = used
+                    .iter()
+                    .filter(|word| pattern.is_match(&word.word) && dictionary.contains(&word.word))
+                    .count();
+                entropy += count.saturating_sub(placed);
+            }
+        }
 
-        return random_words;
+        entropy
     }
 
-    pub fn calculate_entropy_for_acell(
-        row: usize, col: usize, idx: usize,
-        directional_sequences: HashMap<Direction, String>,
-        dictionary: &Trie,
-        used: Vec<String>,
-        sequence_cache: &mut HashMap<String, HashSet<String>>,
+    pub fn words_for_acell(
+        coords: (usize, usize),
+        directional_sequences: BTreeMap<Direction, String>,
+        directions: &[Direction],
+        dictionary: &dyn Dictionary,
+        used: &[Word],
+        short_words: bool,
     ) -> Vec<Word> {
-        // For each row and col (each cell) traverse the position in all directions
         let mut words: Vec<Word> = Vec::new();
 
-        // From default sequence we might have
         for (direction, sequence) in directional_sequences {
-            // for sequences lower than MAX we won't compile
-            if (sequence.len() >= MAX) {
-                // FIXME : Traversing all positions again is unnecessary
-                // for depth in MAX..(sequence.len() + 1) {
-                    // FIXME temporary to establish longest word will be selected
-                    // let subsequence = &sequence[..depth];
-                    let subsequence = &sequence;
-                    let trie_search_result = sequence_cache.entry(subsequence.to_string()).or_insert(dictionary.search(subsequence));
-
-                    let words_processed: Vec<Word> = trie_search_result
-                        .iter()
-                        .filter(|word| !used.contains(word))
-                        .map(|word| Word::new(word.clone(), direction, (row, col)))
-                        .collect();
-
-                    words.extend(words_processed);
-                // }
+            if sequence.chars().count() >= MAX && directions.contains(&direction) {
+                // Search results are sorted so the same seed always produces the same order of words
+                let mut result: Vec<String> = dictionary
+                    .matches(&WFC::sequence_pattern(&sequence, short_words))
+                    .filter(|word| !used.iter().any(|placed| placed.word == *word))
+                    .collect();
+                result.sort();
+
+                words.extend(result.into_iter().map(|word| Word::new(word, direction, coords)));
             }
         }
 
         words
     }
 
+    // Short words match any prefix of the sequence of at least MAX letters, so they can end before a wall
+    fn sequence_pattern(sequence: &str, short_words: bool) -> Pattern {
+        Pattern::sequence(sequence).with_prefixes(short_words).with_min_length(MAX)
+    }
+
     // pub fn printEntropies(&self, entropies: &Vec<HashSet<Word>>) {
         // for row in 0..self.rows {
         //     for col in 0..self.cols {
@@ -107,4 +152,73 @@ impl WFC {
         // }
         // println!();
     // }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::entities::trie::Trie;
+    use crate::test_utils::{placed, trie};
+
+    fn states(words: &[&str]) -> BTreeMap<usize, usize> {
+        BTreeMap::from([(0, words.len())])
+    }
+
+    #[test]
+    fn test_common_words_first() {
+        let mut trie = Trie::new();
+        trie.insert_with_frequency("aalborg", 0.0).unwrap();
+        trie.insert_with_frequency("house", 250.0).unwrap();
+        trie.insert("abbot").unwrap();
+
+        for seed in 0..20 {
+            let words = WFC::find_random_lowest_entropy_words(&states(&["aalborg", "abbot", "house"]), &trie, &mut ChaCha8Rng::seed_from_u64(seed), |_| placed(&["aalborg", "abbot", "house"])).unwrap();
+
+            assert_eq!(words[0].word, "house");
+            assert_eq!(words.len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_without_frequencies() {
+        let trie = trie(&["house", "abbot"]);
+
+        let words = WFC::find_random_lowest_entropy_words(&states(&["abbot", "house"]), &trie, &mut ChaCha8Rng::seed_from_u64(1), |_| placed(&["abbot", "house"])).unwrap();
+        let mut found: Vec<String> = words.into_iter().map(|word| word.word).collect();
+        found.sort();
+
+        assert_eq!(found, vec!["abbot", "house"]);
+    }
+
+    #[test]
+    fn test_entropy_is_counted() {
+        let trie = trie(&["star", "stare", "stop", "slot", "spot"]);
+        let sequences = BTreeMap::from([(Direction::EAST(), String::from("s?o?")), (Direction::SOUTH(), String::from("st???"))]);
+        let used = placed(&["stop", "story"]);
+        let mut cache: HashMap<String, usize> = HashMap::new();
+
+        let entropy = WFC::calculate_entropy_for_acell(sequences.clone(), &Direction::ALL(), &trie, &used, true, &mut cache);
+        let found = WFC::words_for_acell((0, 0), sequences, &Direction::ALL(), &trie, &used, true);
+
+        // slot, spot to the east and star, stare to the south, stop is already on board
+        assert_eq!(entropy, 4);
+        assert_eq!(found.len(), entropy);
+        assert_eq!(cache["s?o?"], 3);
+    }
+
+    #[test]
+    fn test_words_only_for_lowest_entropy() {
+        let trie = trie(&["house"]);
+        let states = BTreeMap::from([(0, 5), (1, 0), (2, 1), (3, 1)]);
+        let mut searched: Vec<usize> = Vec::new();
+
+        let found = WFC::find_random_lowest_entropy_words(&states, &trie, &mut ChaCha8Rng::seed_from_u64(1), |cell| {
+            searched.push(cell);
+            placed(&["house"])
+        }).unwrap();
+
+        assert_eq!(searched, vec![2, 3]);
+        assert_eq!(found.len(), 2);
+    }
+}
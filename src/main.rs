@@ -1,55 +1,300 @@
-use std::time::SystemTime;
-use crate::logic::controller::Controller;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::process::ExitCode;
+use clap::Parser;
+use crosswordo_rust::{solve, CrosswordoError, Dictionary, Filler, MAX, Puzzle, PuzzleBuilder};
+use crosswordo_rust::entities::packed_trie::PackedTrie;
+use crosswordo_rust::entities::template::{Template, BLOCKED, WILDCARD};
+use crosswordo_rust::logic::solver::{parse_grid, parse_words};
+use crosswordo_rust::render::svg::{render_answer_key, render_puzzle, SvgOptions};
 use unidecode::unidecode;
-use rand::seq::SliceRandom;
-use rand::thread_rng;
-mod entities;
-mod logic;
-
-static  BOARD: [[char; 5]; 5] = [
-    ['?', '?', 'b', '?', 'i'],
-    ['?', '?', 'k', '?', '?'],
-    ['?', '?', '?', 'e', '?'],
-    ['?', '?', '?', '?', '?'],
-    ['?', '?', '?', '?', '?']
-];
-
-fn read_dictionary(filename: &str) -> Vec<String> {
-    std::fs::read_to_string(filename)
-        .unwrap()  // panic on possible file-reading errors
+use crate::cli::{Cli, CompileDictArgs, Command, OutputFormat, SolveArgs};
+mod cli;
+
+// Word per line, optionally followed by a tab separated frequency or score, further columns are ignored
+fn read_dictionary(filename: &Path) -> std::io::Result<Vec<(String, Option<f32>)>> {
+    std::fs::read_to_string(filename)?
         .lines()  // split the string into an iterator of string slices
-        .map(|word| word.split("\t").nth(0).unwrap())
-        .map(String::from)  // make each slice into a string
-        .filter(|s| s.len() >= MAX)
+        .enumerate()
+        .map(|(idx, line)| parse_entry(line).map_err(|error| Error::new(ErrorKind::InvalidData, format!("line {}: {}", idx + 1, error))))
+        .filter(|entry| entry.as_ref().map_or(true, |(word, _)| word.chars().count() >= MAX))
         .collect()  // gather them together into a vector
 }
 
-const MAX: usize = 3;
+fn parse_entry(line: &str) -> Result<(String, Option<f32>), String> {
+    let mut columns = line.split('\t');
+    let word = columns.next().unwrap_or_default().to_string();
+
+    let frequency = match columns.next().map(str::trim).filter(|column| !column.is_empty()) {
+        Some(column) => match column.parse::<f32>() {
+            Ok(frequency) if frequency.is_finite() && frequency >= 0.0 => Some(frequency),
+            _ => return Err(format!("'{}' is not a non-negative frequency", column)),
+        },
+        None => None,
+    };
 
-fn main() {
+    Ok((word, frequency))
+}
 
-    // let dictionary = vec![String::from("space"), String::from("place"), String::from("craze"), String::from("crate"), String::from("state"), String::from("plate"), String::from("blade"), String::from("blato"), String::from("care"), String::from("spar")];
-    let mut dictionary: Vec<String> = read_dictionary("./res/en.dr");
+// Words of every dictionary file lowercased, transliterated to ASCII and filtered by the word length options,
+// with the highest frequency given to each of them
+fn load_dictionary(cli: &Cli) -> Result<(Vec<String>, HashMap<String, f32>), String> {
+    let mut dictionary: Vec<(String, Option<f32>)> = Vec::new();
+    for path in cli.dictionary_paths() {
+        let words = read_dictionary(&path).map_err(|error| format!("Could not read dictionary {}: {}", path.display(), error))?;
+        dictionary.extend(words);
+    }
 
     // filter using ruleset
-    let filtered_dictionary: Vec<String> = dictionary
-        .choose_multiple(&mut thread_rng(), dictionary.len())
-        .cloned()
-        .map(|stn| unidecode(stn.to_lowercase().as_str()))
-        .filter(|e| e.len() >= MAX)
-        .collect::<Vec<String>>()[0..].to_vec();
-
-    const ROWS: usize = 4;
-    const COLS: usize = 6;
-
-    let mut controller = Controller::new("stolicka", ROWS, COLS, filtered_dictionary);
-
-
-    // for i in 0..3 {
-    let mut i = 0;
-    // loop {
-    // for _ in 0..20 {
-        controller.perform_action();
-        // i+=1;
-    // }
-}
\ No newline at end of file
+    let mut words: Vec<String> = Vec::new();
+    let mut frequencies: HashMap<String, f32> = HashMap::new();
+    for (word, frequency) in dictionary {
+        let word = unidecode(word.to_lowercase().as_str());
+        if !cli.accepts_length(word.chars().count()) {
+            continue;
+        }
+
+        if let Some(frequency) = frequency {
+            frequencies.entry(word.clone()).and_modify(|current| *current = current.max(frequency)).or_insert(frequency);
+        }
+        words.push(word);
+    }
+
+    Ok((words, frequencies))
+}
+
+// Template letters go through the same transliteration as dictionary words, otherwise no word could cover them
+fn transliterate_template(template: Template) -> Result<Template, CrosswordoError> {
+    let cells = template.cells
+        .iter()
+        .map(|row| row.iter().map(|c| transliterate_letter(*c)).collect::<Result<Vec<char>, CrosswordoError>>())
+        .collect::<Result<Vec<Vec<char>>, CrosswordoError>>()?;
+
+    Template::new(cells)
+}
+
+fn transliterate_letter(c: char) -> Result<char, CrosswordoError> {
+    if c == WILDCARD || c == BLOCKED {
+        return Ok(c);
+    }
+
+    let transliterated = unidecode(&c.to_string());
+    let mut letters = transliterated.chars();
+    match (letters.next(), letters.next()) {
+        (Some(letter), None) => Ok(letter),
+        _ => Err(CrosswordoError::InvalidTemplate(format!("letter '{}' has no single letter transliteration", c))),
+    }
+}
+
+// Writes the processed dictionary into a binary image which is memory mapped by --compiled-dictionary
+fn compile_dictionary(cli: &Cli, args: &CompileDictArgs) -> ExitCode {
+    let compiled = load_dictionary(cli)
+        .and_then(|(words, frequencies)| PackedTrie::weighted(&words, &frequencies).map_err(|error| error.to_string()))
+        .and_then(|trie| trie.write(&args.output).map(|_| trie).map_err(|error| error.to_string()));
+
+    match compiled {
+        Ok(trie) => {
+            println!("{} words compiled into {}", trie.len(), args.output.display());
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn render(puzzle: &Puzzle, format: OutputFormat, options: &SvgOptions) -> Result<String, CrosswordoError> {
+    match format {
+        OutputFormat::Text => Ok(format!("{}\n", puzzle)),
+        OutputFormat::Json => puzzle.to_json().map(|json| json + "\n"),
+        OutputFormat::Svg => Ok(render_puzzle(puzzle, options)),
+    }
+}
+
+// Prints the report of an externally supplied grid, fails when any word is missing or ambiguous
+fn run_solver(args: &SolveArgs) -> ExitCode {
+    let grid = std::fs::read_to_string(&args.grid)
+        .map_err(|error| error.to_string())
+        .and_then(|content| parse_grid(&content).map_err(|error| error.to_string()));
+
+    let grid = match grid {
+        Ok(grid) => grid,
+        Err(error) => {
+            eprintln!("Could not read grid {}: {}", args.grid.display(), error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let words: Vec<String> = match std::fs::read_to_string(&args.words) {
+        Ok(content) => parse_words(&content),
+        Err(error) => {
+            eprintln!("Could not read words {}: {}", args.words.display(), error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let report = solve(&grid, &words, &args.allowed_directions());
+    println!("{}", report);
+
+    if report.is_valid() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    // RUST_LOG takes precedence over the verbosity flags
+    env_logger::Builder::new()
+        .filter_level(cli.log_level())
+        .parse_default_env()
+        .init();
+
+    match &cli.command {
+        Some(Command::Solve(args)) => return run_solver(args),
+        Some(Command::CompileDict(args)) => return compile_dictionary(&cli, args),
+        None => {}
+    }
+
+    let compiled = match cli.compiled_dictionary.as_deref().map(PackedTrie::open).transpose() {
+        Ok(compiled) => compiled,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Compiled dictionary is searched in place, words are listed from it only when the filler needs them
+    let (filtered_dictionary, frequencies) = match &compiled {
+        Some(_) => (Vec::new(), HashMap::new()),
+        None => match load_dictionary(&cli) {
+            Ok(dictionary) => dictionary,
+            Err(error) => {
+                eprintln!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+
+    let required_words: Vec<String> = cli.required_words
+        .iter()
+        .map(|word| unidecode(word.to_lowercase().as_str()))
+        .collect();
+
+    let filler = cli.filler.as_ref().map(|alphabet| match alphabet {
+        Some(alphabet) => Filler::new(alphabet),
+        None => match &compiled {
+            Some(trie) => Filler::from_dictionary(&trie.words().collect::<Vec<String>>()),
+            None => Filler::from_dictionary(&filtered_dictionary),
+        },
+    });
+
+    let builder = match compiled {
+        Some(trie) => PuzzleBuilder::from_dictionary(Box::new(trie)),
+        None => PuzzleBuilder::new(filtered_dictionary).frequencies(frequencies),
+    };
+    let mut builder = builder
+        .size(cli.rows, cli.cols)
+        .solution(&cli.solution)
+        .dictionary_backend(cli.dictionary_backend())
+        .placement(cli.placement.into())
+        .directions(&cli.allowed_directions())
+        .required_words(&required_words)
+        .budget(cli.budget())
+        .allow_partial(cli.partial)
+        .reject_duplicates(cli.reject_duplicates)
+        .crossings(!cli.no_crossings)
+        .strict_solution(!cli.solution_crossings)
+        .short_words(cli.short_words);
+    if let Some(seed) = cli.seed {
+        builder = builder.seed(seed);
+    }
+    if let Some(density) = cli.overlap_density {
+        builder = builder.overlap_density(density);
+    }
+    if let Some(filler) = filler {
+        match filler {
+            Ok(filler) => builder = builder.filler(filler),
+            Err(error) => {
+                eprintln!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    if let Some(path) = &cli.template {
+        let template = std::fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|content| Template::parse(&content).and_then(transliterate_template).map_err(|error| error.to_string()));
+
+        match template {
+            Ok(template) => builder = builder.template(template),
+            Err(error) => {
+                eprintln!("Could not read template {}: {}", path.display(), error);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let puzzle = builder.generate();
+
+    let puzzle = match puzzle {
+        Ok(puzzle) => puzzle,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let svg_options = cli.svg_options();
+    let rendered = match render(&puzzle, cli.format, &svg_options) {
+        Ok(rendered) => rendered,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    match &cli.output {
+        Some(path) => {
+            if let Err(error) = std::fs::write(path, rendered) {
+                eprintln!("Could not write puzzle to {}: {}", path.display(), error);
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{}", rendered),
+    }
+
+    if let Some(path) = &cli.answer_key {
+        if let Err(error) = std::fs::write(path, render_answer_key(&puzzle, &svg_options)) {
+            eprintln!("Could not write answer key to {}: {}", path.display(), error);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entry() {
+        assert_eq!(parse_entry("aalborg"), Ok((String::from("aalborg"), None)));
+        assert_eq!(parse_entry("house\t1520.5\tnoun"), Ok((String::from("house"), Some(1520.5))));
+        assert_eq!(parse_entry("house\t\tnoun"), Ok((String::from("house"), None)));
+        assert!(parse_entry("house\tnoun").is_err());
+        assert!(parse_entry("house\t-3").is_err());
+    }
+
+    #[test]
+    fn test_transliterate_template() {
+        let template = transliterate_template(Template::parse("?č#\nšťa").unwrap()).unwrap();
+
+        assert_eq!(template.cells, vec![vec!['?', 'c', '#'], vec!['s', 't', 'a']]);
+        assert!(transliterate_template(Template::parse("?ß?").unwrap()).is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use std::fmt::Write;
+use crate::entities::puzzle::Puzzle;
+use crate::entities::template::BLOCKED;
+
+// Layout of rendered pages, all sizes are in SVG user units (pixels)
+#[derive(Clone, Debug)]
+pub struct SvgOptions {
+    pub font_family: String,
+    // Size of letters in grid
+    pub font_size: f32,
+    // Size of words in word bank
+    pub bank_font_size: f32,
+    pub cell_size: f32,
+    pub margin: f32,
+    pub bank_columns: usize,
+    pub highlight_color: String,
+    pub line_color: String,
+    // Color of filler letters in answer key
+    pub filler_color: String,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            font_family: String::from("Helvetica, Arial, sans-serif"),
+            font_size: 24.0,
+            bank_font_size: 16.0,
+            cell_size: 40.0,
+            margin: 40.0,
+            bank_columns: 3,
+            highlight_color: String::from("#ffe08a"),
+            line_color: String::from("#e0524a"),
+            filler_color: String::from("#9e9e9e"),
+        }
+    }
+}
+
+// Puzzle page with the letter grid, alphabetically sorted word bank beneath it and the solution hint at the bottom
+pub fn render_puzzle(puzzle: &Puzzle, options: &SvgOptions) -> String {
+    let mut words: Vec<&str> = puzzle.words.iter().map(|word| word.word.as_str()).collect();
+    words.sort();
+    words.dedup();
+
+    let line_height = options.bank_font_size * 1.5;
+    let bank_columns = options.bank_columns.max(1);
+    let bank_rows = words.len().div_ceil(bank_columns);
+    let bank_top = options.margin + grid_height(puzzle, options) + options.cell_size;
+    let hint_top = bank_top + (bank_rows + 1) as f32 * line_height;
+    let height = hint_top + options.margin;
+
+    let mut svg = open_page(page_width(puzzle, options), height);
+    write_grid(&mut svg, puzzle, options, false);
+
+    let column_width = grid_width(puzzle, options) / bank_columns as f32;
+    for (idx, word) in words.iter().enumerate() {
+        let x = options.margin + (idx / bank_rows) as f32 * column_width;
+        let y = bank_top + (idx % bank_rows) as f32 * line_height;
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" font-family="{}" font-size="{:.1}">{}</text>"#,
+            x, y, escape(&options.font_family), options.bank_font_size, escape(&word.to_uppercase())
+        );
+    }
+
+    write_phrase(&mut svg, &puzzle.hint(), hint_top, options);
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Answer key page with solution cells highlighted, a line drawn through every placed word and the solution beneath
+pub fn render_answer_key(puzzle: &Puzzle, options: &SvgOptions) -> String {
+    let phrase_top = options.margin + grid_height(puzzle, options) + options.cell_size;
+    let mut svg = open_page(page_width(puzzle, options), phrase_top + options.margin);
+
+    for (row, col) in &puzzle.solution_cells {
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"/>"#,
+            options.margin + *col as f32 * options.cell_size, options.margin + *row as f32 * options.cell_size,
+            options.cell_size, options.cell_size, escape(&options.highlight_color)
+        );
+    }
+
+    write_grid(&mut svg, puzzle, options, true);
+
+    for word in &puzzle.words {
+        let (row, col) = word.coords;
+        let length = word.word.chars().count() as i32 - 1;
+        let end_row = row as i32 + word.direction.getRow() * length;
+        let end_col = col as i32 + word.direction.getCol() * length;
+
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="{:.1}" stroke-linecap="round" stroke-opacity="0.4"/>"#,
+            cell_center(col as i32, options), cell_center(row as i32, options),
+            cell_center(end_col, options), cell_center(end_row, options),
+            escape(&options.line_color), options.cell_size * 0.6
+        );
+    }
+
+    write_phrase(&mut svg, puzzle.answer(), phrase_top, options);
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn open_page(width: f32, height: f32) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.1}\" height=\"{:.1}\" viewBox=\"0 0 {:.1} {:.1}\">\n<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+        width, height, width, height
+    )
+}
+
+// Spaces of phrase are preserved so the hint keeps word boundaries
+fn write_phrase(svg: &mut String, phrase: &str, y: f32, options: &SvgOptions) {
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" font-family="{}" font-size="{:.1}" letter-spacing="{:.1}" xml:space="preserve">{}</text>"#,
+        options.margin, y, escape(&options.font_family), options.bank_font_size, options.bank_font_size * 0.2, escape(phrase)
+    );
+}
+
+// Filler letters are dimmed in answer key so the words stand out
+fn write_grid(svg: &mut String, puzzle: &Puzzle, options: &SvgOptions, answer_key: bool) {
+    let _ = writeln!(
+        svg,
+        r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="none" stroke="black" stroke-width="2"/>"#,
+        options.margin, options.margin, grid_width(puzzle, options), grid_height(puzzle, options)
+    );
+
+    for (row, line) in puzzle.grid.iter().enumerate() {
+        for (col, c) in line.iter().enumerate() {
+            if *c == BLOCKED {
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="black"/>"#,
+                    options.margin + col as f32 * options.cell_size, options.margin + row as f32 * options.cell_size,
+                    options.cell_size, options.cell_size
+                );
+                continue;
+            }
+
+            let letter = puzzle.grid[row][col];
+            let color = if answer_key && puzzle.filler_cells.contains(&(row, col)) { options.filler_color.as_str() } else { "black" };
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" font-family="{}" font-size="{:.1}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                cell_center(col as i32, options), cell_center(row as i32, options),
+                escape(&options.font_family), options.font_size, escape(color), escape(&letter.to_uppercase().to_string())
+            );
+        }
+    }
+}
+
+fn cell_center(idx: i32, options: &SvgOptions) -> f32 {
+    options.margin + (idx as f32 + 0.5) * options.cell_size
+}
+
+fn grid_width(puzzle: &Puzzle, options: &SvgOptions) -> f32 {
+    puzzle.cols as f32 * options.cell_size
+}
+
+fn grid_height(puzzle: &Puzzle, options: &SvgOptions) -> f32 {
+    puzzle.rows as f32 * options.cell_size
+}
+
+fn page_width(puzzle: &Puzzle, options: &SvgOptions) -> f32 {
+    grid_width(puzzle, options) + 2.0 * options.margin
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::direction::Direction;
+    use crate::entities::word::Word;
+
+    fn puzzle() -> Puzzle {
+        Puzzle {
+            rows: 4,
+            cols: 4,
+            grid: vec![
+                vec!['c', 'o', 'l', 'd'],
+                vec!['a', 'h', '#', 'o'],
+                vec!['t', 'r', 'e', 'e'],
+                vec!['s', 'u', 'n', 'i'],
+            ],
+            words: vec![
+                Word::new(String::from("tree"), Direction::EAST(), (2, 0)),
+                Word::new(String::from("cats"), Direction::SOUTH(), (0, 0)),
+                Word::new(String::from("cold"), Direction::EAST(), (0, 0)),
+            ],
+            solution: String::from("hi"),
+            phrase: String::from("Hi!"),
+            solution_cells: vec![(1, 1), (3, 3)],
+            filler_cells: Vec::new(),
+            overlaps: 2,
+            seed: 0,
+        }
+    }
+
+    #[test]
+    fn test_word_bank_sorted() {
+        let svg = render_puzzle(&puzzle(), &SvgOptions::default());
+
+        let cats = svg.find(">CATS<").unwrap();
+        let cold = svg.find(">COLD<").unwrap();
+        let tree = svg.find(">TREE<").unwrap();
+        assert!(cats < cold && cold < tree);
+    }
+
+    #[test]
+    fn test_grid_letters() {
+        let svg = render_puzzle(&puzzle(), &SvgOptions::default());
+
+        // Blocked cell is drawn as a filled rectangle instead of a letter
+        assert_eq!(svg.matches("dominant-baseline").count(), 15);
+        assert!(svg.contains(">H<"));
+        assert!(svg.contains(">I<"));
+    }
+
+    #[test]
+    fn test_answer_key() {
+        let options = SvgOptions { cell_size: 10.0, margin: 5.0, ..SvgOptions::default() };
+        let svg = render_answer_key(&puzzle(), &options);
+
+        assert_eq!(svg.matches("<line").count(), 3);
+        assert_eq!(svg.matches(&format!("fill=\"{}\"", options.highlight_color)).count(), 2);
+        // TREE goes from the first to the last cell of third row
+        assert!(svg.contains(r#"x1="10.0" y1="30.0" x2="40.0" y2="30.0""#));
+    }
+
+    #[test]
+    fn test_filler_dimmed_in_answer_key() {
+        let mut puzzle = puzzle();
+        puzzle.grid[1][2] = 'x';
+        puzzle.filler_cells = vec![(1, 2)];
+        let options = SvgOptions::default();
+
+        let filler = format!("fill=\"{}\" text-anchor", options.filler_color);
+        assert_eq!(render_answer_key(&puzzle, &options).matches(&filler).count(), 1);
+        assert!(!render_puzzle(&puzzle, &options).contains(&filler));
+    }
+
+    #[test]
+    fn test_phrase() {
+        let options = SvgOptions::default();
+
+        assert!(render_puzzle(&puzzle(), &options).contains(r#"xml:space="preserve">__!</text>"#));
+        assert!(render_answer_key(&puzzle(), &options).contains(r#"xml:space="preserve">Hi!</text>"#));
+    }
+
+    #[test]
+    fn test_escape_font() {
+        let options = SvgOptions { font_family: String::from("\"Comic\" & Co"), ..SvgOptions::default() };
+        let svg = render_puzzle(&puzzle(), &options);
+
+        assert!(svg.contains("&quot;Comic&quot; &amp; Co"));
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::OnceLock;
+use crate::entities::direction::Direction;
+use crate::entities::trie::Trie;
+use crate::entities::word::Word;
+
+// Fixtures shared by the tests of every module
+
+pub fn words(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| word.to_string()).collect()
+}
+
+pub fn trie(words: &[&str]) -> Trie {
+    let mut trie = Trie::new();
+    for word in words {
+        trie.insert(word).unwrap();
+    }
+    trie
+}
+
+// Words placed east from the top left corner, for tests where the position does not matter
+pub fn placed(words: &[&str]) -> Vec<Word> {
+    words.iter().map(|word| Word::new(word.to_string(), Direction::EAST(), (0, 0))).collect()
+}
+
+// Lowercase ASCII words of the bundled English dictionary, the file is read once for all tests
+pub fn en_dictionary() -> Vec<String> {
+    static DICTIONARY: OnceLock<Vec<String>> = OnceLock::new();
+
+    DICTIONARY
+        .get_or_init(|| {
+            std::fs::read_to_string("./res/en.dr")
+                .unwrap()
+                .lines()
+                .map(String::from)
+                .filter(|word| word.len() >= crate::MAX && word.chars().all(|c| c.is_ascii_lowercase()))
+                .collect()
+        })
+        .clone()
+}